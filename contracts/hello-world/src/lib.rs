@@ -1,8 +1,41 @@
-get_player_earnings#![no_std]
+#![no_std]
 use soroban_sdk::{
-    contract, contractimpl, contracttype, contracterror, contractclient, vec, Env, String, Vec, Address, BytesN, Bytes, token
+    contract, contractimpl, contracttype, contracterror, contractclient, panic_with_error, symbol_short, vec, Env, String, Vec, Map, Address, BytesN, Bytes, Symbol, IntoVal, token,
+    auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
+    xdr::ToXdr
 };
 
+// Ledgers a queued distribution policy change must wait (~1 day at 5s per ledger)
+const POLICY_TIMELOCK_LEDGERS: u32 = 17_280;
+
+// Ledgers a member must have been in a circle before betraying it, unless the admin changes it (~1 hour)
+const DEFAULT_MIN_BETRAYAL_TENURE: u32 = 720;
+
+// Protocol cap on the keeper tip, so the admin can't route circles' harvests to themselves (3%)
+const MAX_KEEPER_TIP_BPS: u32 = 300;
+
+// Number of recent harvests a betrayal settlement can be measured against
+const RECENT_HARVEST_WINDOW: u32 = 10;
+
+// Fixed-point scale for vault share prices (KALE has 7 decimals)
+const SHARE_PRICE_SCALE: i128 = 10_000_000;
+
+// Restitution that buys back one trust point (1,000 KALE)
+const RESTITUTION_PER_TRUST_POINT: i128 = 1_000 * SHARE_PRICE_SCALE;
+
+// Trust scores are stored in hundredths of a point
+const TRUST_SCALE: i128 = 100;
+
+// KALE amount that per-KALE trust rates are expressed against (1,000 KALE)
+const TRUST_KALE_UNIT: i128 = 1_000 * SHARE_PRICE_SCALE;
+
+// Entries kept in each sorted leaderboard index
+const LEADERBOARD_SIZE: u32 = 100;
+
+// Persistent entries are extended to ~30 days whenever they are written with less than ~7 days left
+const PERSISTENT_TTL_THRESHOLD: u32 = 120_960;
+const PERSISTENT_TTL_EXTEND_TO: u32 = 518_400;
+
 // KALE Mining Contract Interface - Based on the actual contract code
 #[contractclient(name = "KaleMiningClient")]
 pub trait KaleMiningInterface {
@@ -16,6 +49,103 @@ pub trait KaleMiningInterface {
     fn harvest(env: Env, farmer: Address, index: u32) -> i128;
 }
 
+// How a circle's pooled harvest is split
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum DistributionPolicy {
+    Equal,                  // Same share for every member
+    ProRata,                // Proportional to what each member harvested into the pool
+    TenureWeighted,         // Proportional to how long each member has been in the circle
+    CreatorCommission(u32), // Creator takes the given basis points, the rest is split equally
+    WinnerTakesAll,         // One member takes everything, rotating by harvest index
+}
+
+// What a betrayer is entitled to
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum BetrayalRule {
+    PerpetualRedirect,       // Every future harvest goes to the betrayer (original behaviour)
+    TakeRecentHarvests(u32), // Betrayer is owed these basis points of the recent harvests, paid from the next ones
+}
+
+// What happens to the circle once the betrayer has been settled
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum AfterBetrayal {
+    Freeze,              // The circle stops harvesting
+    MembersKeepEarnings, // Remaining members keep splitting future harvests
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct RevengeTerms {
+    pub window_ledgers: u32, // Ledgers after the first betrayal during which victims may retaliate
+    pub clawback_bps: u32,   // Share of the betrayers' held rewards clawed back by a retaliation
+    pub deposit: i128,       // KALE each betrayer or mutiny voter must post, slashed by a retaliation
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct MutinyConfig {
+    pub quorum_bps: u32,     // Fraction of members, in basis points, that must vote to betray
+    pub voting_window: u32,  // Ledgers after the first vote before an unfinished mutiny expires
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct MutinyVotes {
+    pub voters: Vec<Address>,
+    pub opened_at: u32,      // Ledger of the first vote in this round
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct MutinyStatus {
+    pub circle_id: u32,
+    pub voters: Vec<Address>,  // Current members who voted in the running round
+    pub votes_needed: u32,
+    pub opened_at: u32,
+    pub expires_at: u32,
+    pub active: bool,          // False when there is no vote running or it has expired
+}
+
+// How several betrayals within the window are resolved
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum MultiBetrayalRule {
+    ShareSpoils,      // All betrayers split what the betrayal pays
+    PrisonersDilemma, // Betrayers share, but if every member defects nobody gets anything and the held spoils go back to the creator
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct BetrayalWindow {
+    pub window_ledgers: u32, // Ledgers after the first betrayal during which others may join it; betrayer payouts are held until then
+    pub rule: MultiBetrayalRule,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct BetrayalTerms {
+    pub rule: BetrayalRule,
+    pub after: AfterBetrayal, // Ignored for perpetual redirection
+}
+
+// A hidden betrayal waiting to be revealed
+#[derive(Clone)]
+#[contracttype]
+pub struct BetrayalCommitment {
+    pub commitment: BytesN<32>, // sha256(salt ‖ betrayer address XDR)
+    pub committed_at: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct PendingPolicy {
+    pub policy: DistributionPolicy,
+    pub effective_ledger: u32, // Ledger from which the change can be applied
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub struct Circle {
@@ -26,6 +156,17 @@ pub struct Circle {
     pub member_count: u32,
     pub betrayer: Option<Address>, // Track who betrayed the circle
     pub total_kale_earned: i128,   // New: Total KALE earned by this circle
+    pub policy: DistributionPolicy, // How harvests are split among members
+}
+
+// How the indivisible part of an equal split is handled
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum RemainderStrategy {
+    FirstMember,   // Always pay the first member (original behaviour)
+    RotateByIndex, // Pay members in turn, by harvest index
+    CarryForward,  // Keep the dust and add it to the next harvest
+    Random,        // Pay a member picked with the ledger PRNG
 }
 
 #[derive(Clone)]
@@ -37,6 +178,10 @@ pub struct CircleInfo {
     pub creator: Address,
     pub member_count: u32,
     pub total_kale_earned: i128, // New: Include earnings in circle info
+    pub policy: DistributionPolicy,
+    pub join_bond: i128,     // KALE each new member must post when joining
+    pub total_bonded: i128,  // Bonds currently held for the circle's members
+    pub closed: bool,
 }
 
 #[derive(Clone)]
@@ -45,6 +190,62 @@ pub struct HarvestResult {
     pub total_distributed: i128,
     pub successful_circles: u32,
     pub failed_harvests: u32,
+    pub circles: Vec<CircleHarvestReport>, // Per-circle breakdown of this harvest
+    pub keeper_tip: i128,                  // KALE paid to the caller for running the harvest
+}
+
+// Why a member could not be harvested into the pool
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum HarvestFailureReason {
+    MiningError,      // The mining contract rejected the harvest
+    BalanceReadError, // The member's KALE balance could not be read
+    TransferError,    // Moving the harvest into the pool failed
+    AllowanceExpired, // The member's allowance to the contract no longer covers the harvest
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct MemberPull {
+    pub member: Address,
+    pub amount: i128,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct MemberPayout {
+    pub member: Address,
+    pub amount: i128,
+    pub escrowed: bool, // Not transferred: the amount went to pending credits or is held for a betrayal or revenge window
+    pub compounded: i128, // Share held back for the member to plant through plant_compounded
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct MemberFailure {
+    pub member: Address,
+    pub reason: HarvestFailureReason,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct RemainderOutcome {
+    pub amount: i128,
+    pub recipient: Option<Address>, // None when the remainder was carried forward or there was none
+    pub carried_forward: bool,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct CircleHarvestReport {
+    pub circle_id: u32,
+    pub pulls: Vec<MemberPull>,         // Members harvested and the amount pooled from each
+    pub payouts: Vec<MemberPayout>,
+    pub failures: Vec<MemberFailure>,
+    pub remainder: RemainderOutcome,    // Zero amount when the split was exact
+    pub total_pooled: i128,
+    pub total_distributed: i128,
+    pub keeper_tip: i128,
 }
 
 // New earnings tracking structures
@@ -56,6 +257,10 @@ pub struct PlayerEarnings {
     pub kale_earned_from_own_circles: i128,
     pub kale_earned_from_join_circles: i128,
     pub kale_earned_from_betrayals: i128,
+    pub kale_compounded: i128, // Earnings planted back into the mining contract
+    pub kale_from_retaliation: i128, // Clawed back from betrayers as a victim
+    pub kale_lost_to_retaliation: i128, // Clawed back or slashed as a betrayer
+    pub kale_from_restitution: i128, // Paid back by betrayers as a victim
 }
 
 #[derive(Clone)]
@@ -68,6 +273,108 @@ pub struct CircleEarnings {
     pub last_harvest_amount: i128,
 }
 
+// Tip paid to whoever calls the harvest
+#[derive(Clone)]
+#[contracttype]
+pub struct KeeperConfig {
+    pub tip_bps: u32,           // Cut of each successful circle's pooled harvest
+    pub max_tip_per_call: i128, // Cap on the total tip of a single call
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct KeeperStats {
+    pub address: Address,
+    pub total_tips: i128,
+    pub harvest_calls: u32,
+    pub circles_harvested: u32,
+}
+
+// Outcome of planting for a member through their circle
+#[derive(Clone)]
+#[contracttype]
+pub struct PlantStatus {
+    pub member: Address,
+    pub consented: bool,
+    pub amount: i128,
+    pub round_ledger: u32, // Ledger sequence (not mining block index) of the planting round this status belongs to
+    pub planted: bool,
+}
+
+// Outcome of relaying one farmer's proof of work
+#[derive(Clone)]
+#[contracttype]
+pub struct WorkResult {
+    pub farmer: Address,
+    pub success: bool,
+    pub zeros: u32, // Leading zeros reported by the mining contract, 0 on failure
+}
+
+// Vault of a pooled circle, where the contract farms as a single farmer
+#[derive(Clone)]
+#[contracttype]
+pub struct Vault {
+    pub total_assets: i128, // KALE owned by the vault, including what is planted
+    pub total_shares: i128,
+    pub planted: i128,      // KALE currently staked in the mining contract
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct VaultInfo {
+    pub circle_id: u32,
+    pub total_assets: i128,
+    pub total_shares: i128,
+    pub planted: i128,
+    pub share_price: i128, // KALE per share, scaled by SHARE_PRICE_SCALE
+}
+
+// Whether a harvest would be distributed at all
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum PreviewStatus {
+    Ready,        // The circle harvests normally
+    CircleFrozen, // Frozen after a betrayal, harvests skip it
+    CircleClosed, // Closed by its creator, harvests skip it
+    PooledVault,  // Farms through its vault instead of member harvests
+}
+
+// What a harvest would pay out, computed without moving tokens
+#[derive(Clone)]
+#[contracttype]
+pub struct DistributionPreview {
+    pub circle_id: u32,
+    pub status: PreviewStatus,                // Anything but Ready comes with an empty preview
+    pub total: i128,                          // Hypothetical harvest plus carried dust
+    pub payouts: Vec<MemberPayout>,
+    pub keeper_tip: i128,                     // Largest tip a keeper could take from this harvest
+    pub betrayal_recipient: Option<Address>,  // First betrayer, set when part of the harvest goes to the betrayers
+    pub remainder: RemainderOutcome,          // Recipient is None for picks that depend on the block index or the PRNG, decided at harvest time
+}
+
+// What a member has put into and taken out of a circle's pool
+#[derive(Clone)]
+#[contracttype]
+pub struct MemberContribution {
+    pub circle_id: u32,
+    pub member: Address,
+    pub total_harvested: i128,       // KALE harvested into the pool
+    pub total_received: i128,        // KALE received from the pool
+    pub harvests_participated: u32,  // Harvests in which the member contributed
+}
+
+// Treasury reconciliation of the contract's KALE balance
+#[derive(Clone)]
+#[contracttype]
+pub struct TreasuryAudit {
+    pub kale_balance: i128,
+    pub total_pooled: i128,          // KALE pulled in from members
+    pub total_distributed: i128,     // KALE paid out by the contract
+    pub total_pending_credits: i128, // KALE held in escrow for members
+    pub total_carried_dust: i128,    // Remainders carried into future harvests
+    pub unexplained_surplus: i128,   // Balance not explained by the counters (dust, stray deposits)
+}
+
 // Enhanced scoreboard structures
 #[derive(Clone)]
 #[contracttype]
@@ -78,6 +385,55 @@ pub struct PlayerStats {
     pub circles_betrayed: u32,
     pub times_betrayed: u32, // How many times this player's circles were betrayed
     pub total_kale_earned: i128, // New: Total KALE earned by this player
+    pub retaliations: u32, // How many times this player led a retaliation against betrayers
+    pub times_retaliated_against: u32, // How many times this player's betrayal was punished
+    pub restitution_paid: i128, // KALE paid back to the victims of this player's betrayals
+}
+
+// Admin-set weights of the trust model, all in hundredths of a point
+#[derive(Clone)]
+#[contracttype]
+pub struct TrustParams {
+    pub version: u32,                    // Bumped on every change; records from older versions are re-seeded
+    pub half_life_ledgers: u32,          // Ledgers after which an action counts half as much
+    pub join_points: i128,               // Gained for joining a circle
+    pub betrayal_points: i128,           // Lost for each betrayal
+    pub betrayal_points_per_unit: i128,  // Lost per TRUST_KALE_UNIT captured by betraying
+    pub tenure_points: i128,             // Gained per payout from an unbetrayed circle at full tenure
+    pub restitution_points_per_unit: i128, // Gained per TRUST_KALE_UNIT paid back as restitution, up to half of what the betrayal cost
+}
+
+// A player's persisted trust score, decayed lazily from the last update
+#[derive(Clone)]
+#[contracttype]
+pub struct TrustRecord {
+    pub score: i128,       // Hundredths of a point as of last_updated
+    pub last_updated: u32,
+    pub version: u32,      // TrustParams version the score was built with
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum LeaderboardMetric {
+    TrustScore,     // Players by trust score in hundredths of a point, as of their last ranked trust change (not decayed since, tenure rewards land with the next one)
+    KaleEarned,     // Players by total KALE earned
+    Betrayals,      // Players by circles betrayed
+    CircleEarnings, // Circles by total KALE earned
+    KeeperTips,     // Keepers by total tips earned
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum LeaderboardSubject {
+    Player(Address),
+    Circle(u32),
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct LeaderboardEntry {
+    pub subject: LeaderboardSubject,
+    pub value: i128, // Metric value as of the subject's last update
 }
 
 #[derive(Clone)]
@@ -88,7 +444,9 @@ pub struct ScoreboardEntry {
     pub circles_joined: u32,
     pub circles_betrayed: u32,
     pub times_betrayed: u32,
-    pub trust_score: i32, // Calculated score: circles_joined - circles_betrayed
+    pub trust_score: i32, // Time-decayed score from the trust model, in whole points
+    pub trust_version: u32, // TrustParams version the score was computed with
+    pub restored_trust: u32, // Trust points bought back with restitution, at most half of what each betrayal cost
     pub betrayal_ratio: u32, // Percentage: (circles_betrayed * 100) / circles_joined (0 if no circles joined)
     pub total_kale_earned: i128, // New: Total KALE earned
     pub kale_per_circle: i128, // New: Average KALE per circle joined
@@ -113,6 +471,82 @@ pub enum DataKey {
     PlayerEarnings(Address), // Address -> PlayerEarnings
     CircleEarnings(u32),    // Circle ID -> CircleEarnings
     TotalKaleEarned,        // Global total KALE earned across all circles
+    // Escrow for payouts that could not be delivered
+    PendingCredit(Address), // Address -> KALE owed from failed payouts
+    TotalPendingCredits,    // Sum of all pending credits
+    // Treasury counters
+    TotalPooled,            // KALE transferred into the contract
+    TotalDistributed,       // KALE transferred out of the contract
+    Admin,                  // Contract administrator
+    // Remainder handling
+    RemainderStrategy(u32), // Circle ID -> RemainderStrategy
+    CarriedDust(u32),       // Circle ID -> remainder carried into the next harvest
+    TotalCarriedDust,       // Sum of carried dust across all circles
+    // Distribution policies
+    PendingPolicy(u32),     // Circle ID -> PendingPolicy waiting out its timelock
+    MemberJoinedAt(u32, Address), // (Circle ID, member) -> ledger the member joined
+    MemberContribution(u32, Address), // (Circle ID, member) -> MemberContribution
+    // Keeper incentives
+    KeeperConfig,           // KeeperConfig set by the admin
+    KeeperStats(Address),   // Keeper -> KeeperStats; kept in persistent storage as it grows with every keeper
+    HarvestedIndex(Address, u32), // (Member, block index) -> harvested through a circle; kept in persistent storage as it grows with every block
+    // Pooled planting
+    PlantConsent(u32, Address), // (Circle ID, member) -> member allows the circle to plant for them
+    PlantStatus(u32, Address),  // (Circle ID, member) -> PlantStatus of the latest round
+    LastPlantRound(u32),        // Circle ID -> ledger sequence of the latest planting round
+    CompoundBps(u32, Address),  // (Circle ID, member) -> share of each payout to re-plant
+    CompoundReserve(Address),   // Member -> payout shares held back for them to plant
+    TotalCompoundReserve,       // Held-back shares across all members
+    // Pooled (circle-as-farmer) mode
+    Vault(u32),                 // Circle ID -> Vault; present only for pooled circles
+    VaultShares(u32, Address),  // (Circle ID, member) -> vault shares held
+    TotalVaultLiquid,           // Unplanted KALE held across all vaults
+    ActiveFarmCircle,           // Circle ID whose stake is currently planted by the contract
+    // Betrayal settlement
+    BetrayalTerms(u32),         // Circle ID -> BetrayalTerms
+    BetrayalDebt(u32),          // Circle ID -> KALE still owed to the betrayer from future harvests
+    RecentHarvests(u32),        // Circle ID -> Vec<i128> of the latest harvest amounts
+    CircleFrozen(u32),          // Circle ID -> circle stopped harvesting after settlement
+    MinBetrayalTenure,          // Ledgers of membership required before betraying
+    // Sealed betrayal
+    SealedBetrayal(u32),        // Circle ID -> reveal delay in ledgers; present only for sealed circles
+    BetrayalCommitment(u32, Address), // (Circle ID, member) -> BetrayalCommitment
+    // Multiple betrayers
+    BetrayalWindow(u32),        // Circle ID -> BetrayalWindow
+    CircleBetrayers(u32),       // Circle ID -> Vec<Address> of everyone who betrayed it
+    FirstBetrayalLedger(u32),   // Circle ID -> ledger of the first betrayal
+    WindowHeldRewards(u32),     // Circle ID -> betrayer payouts held until the betrayal window closes
+    TotalWindowHeld,            // Betrayer payouts held across all circles
+    // Mutiny
+    MutinyConfig(u32),          // Circle ID -> MutinyConfig
+    MutinyVotes(u32),           // Circle ID -> MutinyVotes of the running round
+    // Revenge
+    RevengeTerms(u32),                 // Circle ID -> RevengeTerms
+    HeldBetrayalRewards(u32, Address), // (Circle ID, betrayer) -> rewards held until the revenge window closes
+    BetrayalDeposit(u32, Address),     // (Circle ID, betrayer) -> deposit posted when betraying
+    TotalRevengeHeld,                  // Held rewards and deposits across all circles
+    Retaliated(u32),                   // Circle ID -> bool, set once victims have retaliated
+    // Join bonds
+    JoinBond(u32),              // Circle ID -> KALE bond required to join
+    MemberBond(u32, Address),   // (Circle ID, member) -> bond held for the member
+    CircleBonded(u32),          // Circle ID -> bonds held for its members
+    TotalBonds,                 // Bonds held across all circles
+    CircleClosed(u32),          // Circle ID -> bool, set once the creator closes the circle
+    // Restitution
+    Restitution(u32, Address),  // (Circle ID, betrayer) -> KALE paid back to the circle's victims
+    BetrayalTrustCost(u32, Address), // (Circle ID, betrayer) -> trust points the betrayal cost, including captured KALE
+    TrustRestored(u32, Address),     // (Circle ID, betrayer) -> trust points restitution has bought back
+    TotalTrustRestored(Address),     // Player -> trust points restitution has bought back across circles
+    MinTrust(u32),              // Circle ID -> trust score required to join
+    // Lineage
+    Predecessor(u32),           // Circle ID -> betrayed circle it was refounded from
+    Successor(u32),             // Circle ID -> circle refounded from it
+    // Trust model
+    TrustParams,                // TrustParams
+    TrustRecord(Address),       // Player -> TrustRecord
+    JoinTrustAwarded(u32, Address), // (Circle ID, player) -> join points already awarded; kept in persistent storage
+    // Leaderboards
+    Leaderboard(LeaderboardMetric), // Metric -> Vec<LeaderboardEntry>, highest first, at most LEADERBOARD_SIZE; kept in persistent storage as each board is too large for the instance entry
 }
 
 #[contracterror]
@@ -131,21 +565,149 @@ pub enum Error {
     CannotBetrayOwnCircle = 11,
     TokenTransferFailed = 12,
     InvalidAmount = 13,
+    NothingToClaim = 14,
+    PendingCreditsExceedBalance = 15,
+    NotInitialized = 17,
+    SweepExceedsSurplus = 18,
+    InvalidBasisPoints = 19,
+    NoPendingPolicy = 20,
+    PolicyTimelocked = 21,
+    NotAMember = 22,
+    NotPooledCircle = 23,
+    AlreadyPooled = 24,
+    InsufficientShares = 25,
+    VaultIlliquid = 26,
+    FarmerBusy = 27,
+    CircleFrozen = 28,
+    BetrayalTooEarly = 29,
+    SealedBetrayalOnly = 30,
+    NotSealedCircle = 31,
+    NoCommitment = 32,
+    RevealTooEarly = 33,
+    CommitmentMismatch = 34,
+    MutinyOnly = 35,
+    NotMutinyCircle = 36,
+    AlreadyVoted = 37,
+    NoRevengeTerms = 38,
+    RevengeWindowClosed = 39,
+    RevengeWindowOpen = 40,
+    AlreadyRetaliated = 41,
+    CircleClosed = 42,
+    NotABetrayer = 43,
+    TrustTooLow = 44,
+    CircleNotBetrayed = 45,
+    AlreadyRefounded = 46,
+    InvalidTrustParams = 47,
+    BetrayalWindowOpen = 49,
+    PlantFailed = 50,
 }
 
 #[contractimpl]
 impl Contract {
+    /// Bind the contract administrator at deploy time
+    pub fn __constructor(env: Env, admin: Address) {
+        env.storage().instance().set(&DataKey::Admin, &admin);
+    }
+    
+    /// Get the contract administrator
+    pub fn get_admin(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Admin)
+    }
+    
+    /// Configure the tip paid to harvest callers, at most MAX_KEEPER_TIP_BPS (admin only)
+    pub fn set_keeper_config(env: Env, tip_bps: u32, max_tip_per_call: i128) -> Result<(), Error> {
+        Self::require_admin(&env)?;
+        
+        if tip_bps > MAX_KEEPER_TIP_BPS {
+            return Err(Error::InvalidBasisPoints);
+        }
+        
+        if max_tip_per_call < 0 {
+            return Err(Error::InvalidAmount);
+        }
+        
+        let config = KeeperConfig {
+            tip_bps,
+            max_tip_per_call,
+        };
+        env.storage().instance().set(&DataKey::KeeperConfig, &config);
+        
+        Ok(())
+    }
+    
+    /// Get the keeper tip configuration
+    pub fn get_keeper_config(env: Env) -> KeeperConfig {
+        Self::load_keeper_config(&env)
+    }
+    
+    /// Get the harvest statistics of a keeper
+    pub fn get_keeper_stats(env: Env, address: Address) -> Option<KeeperStats> {
+        env.storage().persistent().get(&DataKey::KeeperStats(address))
+    }
+    
+    /// Get the keepers with the most tips earned, highest first
+    pub fn get_keeper_leaderboard(env: Env, limit: u32) -> Vec<KeeperStats> {
+        let mut ranked: Vec<KeeperStats> = vec![&env];
+        
+        for entry in Self::get_leaderboard(env.clone(), LeaderboardMetric::KeeperTips, 0, limit).iter() {
+            if let LeaderboardSubject::Player(keeper) = entry.subject {
+                if let Some(stats) = env.storage().persistent().get::<DataKey, KeeperStats>(&DataKey::KeeperStats(keeper)) {
+                    ranked.push_back(stats);
+                }
+            }
+        }
+        
+        ranked
+    }
+    
+    /// Recover tokens stranded in the contract without touching what members are owed (admin only)
+    pub fn sweep(env: Env, token: Address, to: Address, amount: i128) -> Result<(), Error> {
+        Self::require_admin(&env)?;
+        
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        
+        let token_client = token::Client::new(&env, &token);
+        let balance = Self::safe_get_balance(&token_client, &env.current_contract_address())?;
+        
+        // KALE owed to members is never sweepable
+        let kale_client = Self::get_kale_client(&env)?;
+        let owed = if token == kale_client.address {
+            Self::get_member_liabilities(&env)
+        } else {
+            0
+        };
+        
+        if amount > balance - owed {
+            return Err(Error::SweepExceedsSurplus);
+        }
+        
+        match token_client.try_transfer(&env.current_contract_address(), &to, &amount) {
+            Ok(_) => {},
+            Err(_) => return Err(Error::TokenTransferFailed),
+        }
+        
+        env.events().publish((symbol_short!("sweep"), token), (to, amount));
+        
+        Ok(())
+    }
+    
     pub fn create_circle(env: Env, creator: Address, name: String, password_hash: BytesN<32>) -> Result<u32, Error> {
+        Self::create_circle_with_policy(env, creator, name, password_hash, DistributionPolicy::Equal)
+    }
+
+    /// Create a circle that splits its harvests with the given distribution policy
+    pub fn create_circle_with_policy(env: Env, creator: Address, name: String, password_hash: BytesN<32>, policy: DistributionPolicy) -> Result<u32, Error> {
         creator.require_auth();
         
+        Self::validate_policy(&policy)?;
+        
         // Check if creator has already created a circle
         if env.storage().instance().has(&DataKey::CreatedCircle(creator.clone())) {
             return Err(Error::AlreadyCreatedCircle);
         }
 
-        // Get next available circle ID
-        let circle_id = Self::get_next_circle_id(&env);
-
         // Create the circle with earnings tracking
         let circle = Circle {
             name,
@@ -155,36 +717,11 @@ impl Contract {
             member_count: 1,
             betrayer: None,
             total_kale_earned: 0, // Initialize earnings
+            policy,
         };
-        
-        // Store the circle
-        env.storage().instance().set(&DataKey::Circle(circle_id), &circle);
-        
-        // Mark the creator as having created a circle
-        env.storage().instance().set(&DataKey::CreatedCircle(creator.clone()), &circle_id);
-
-        // Initialize empty members list for this circle
-        let empty_members: Vec<Address> = vec![&env];
-        env.storage().instance().set(&DataKey::CircleMembers(circle_id), &empty_members);
 
-        // Add this circle ID to the list of all circles
-        let mut all_circles: Vec<u32> = env.storage().instance().get(&DataKey::AllCircleIds).unwrap_or(vec![&env]);
-        all_circles.push_back(circle_id);
-        env.storage().instance().set(&DataKey::AllCircleIds, &all_circles);
+        let circle_id = Self::store_new_circle(&env, &circle, &vec![&env]);
 
-        // Increment the circle ID counter
-        env.storage().instance().set(&DataKey::NextCircleId, &(circle_id + 1));
-        
-        // Initialize circle earnings tracking
-        let circle_earnings = CircleEarnings {
-            circle_id,
-            total_earned: 0,
-            total_harvests: 0,
-            average_per_harvest: 0,
-            last_harvest_amount: 0,
-        };
-        env.storage().instance().set(&DataKey::CircleEarnings(circle_id), &circle_earnings);
-        
         // Update scoreboard stats for creator
         Self::update_player_stats_created(&env, &creator);
         
@@ -200,6 +737,88 @@ impl Contract {
         Ok(circle_id)
     }
 
+    /// Refound a betrayed circle as a successor holding everyone but the betrayers, closing the betrayed one (only creator can do this)
+    pub fn refound_circle(env: Env, creator: Address, betrayed_circle_id: u32, new_password_hash: BytesN<32>) -> Result<u32, Error> {
+        creator.require_auth();
+        
+        let predecessor: Circle = match env.storage().instance().get(&DataKey::Circle(betrayed_circle_id)) {
+            Some(c) => c,
+            None => return Err(Error::CircleDoesNotExist),
+        };
+        
+        if predecessor.creator != creator {
+            return Err(Error::NotOwner);
+        }
+        
+        if !predecessor.betrayed {
+            return Err(Error::CircleNotBetrayed);
+        }
+        
+        if env.storage().instance().has(&DataKey::Successor(betrayed_circle_id)) {
+            return Err(Error::AlreadyRefounded);
+        }
+        
+        let members: Vec<Address> = env.storage().instance().get(&DataKey::CircleMembers(betrayed_circle_id)).unwrap_or(vec![&env]);
+        let loyal = Self::loyal_members(&env, betrayed_circle_id, &members);
+        
+        let circle = Circle {
+            name: predecessor.name,
+            password_hash: new_password_hash,
+            betrayed: false,
+            creator: creator.clone(),
+            member_count: 1 + loyal.len(),
+            betrayer: None,
+            total_kale_earned: 0,
+            policy: predecessor.policy,
+        };
+        
+        // The creator's slot moves to the successor; the betrayed circle keeps its history
+        let circle_id = Self::store_new_circle(&env, &circle, &loyal);
+        
+        // Close the betrayed circle so its betrayers stop collecting the loyal members' harvests
+        if !env.storage().instance().has(&DataKey::CircleClosed(betrayed_circle_id)) {
+            Self::mark_closed(&env, betrayed_circle_id);
+        }
+        
+        for member in loyal.iter() {
+            let mut wallet_circles: Vec<u32> = env.storage().instance().get(&DataKey::WalletCircle(member.clone())).unwrap_or(vec![&env]);
+            wallet_circles.push_back(circle_id);
+            env.storage().instance().set(&DataKey::WalletCircle(member.clone()), &wallet_circles);
+            
+            // Members keep the tenure they earned in the predecessor
+            let joined_at: u32 = env.storage().instance().get(&DataKey::MemberJoinedAt(betrayed_circle_id, member.clone())).unwrap_or(0);
+            env.storage().instance().set(&DataKey::MemberJoinedAt(circle_id, member.clone()), &joined_at);
+        }
+        
+        env.storage().instance().set(&DataKey::Predecessor(circle_id), &betrayed_circle_id);
+        env.storage().instance().set(&DataKey::Successor(betrayed_circle_id), &circle_id);
+        
+        Ok(circle_id)
+    }
+    
+    /// Get the chain of circles a circle belongs to, from the original to the latest refounding
+    pub fn get_circle_lineage(env: Env, circle_id: u32) -> Vec<u32> {
+        let mut lineage: Vec<u32> = vec![&env];
+        if !env.storage().instance().has(&DataKey::Circle(circle_id)) {
+            return lineage;
+        }
+        
+        let mut current = circle_id;
+        while let Some(previous) = env.storage().instance().get::<DataKey, u32>(&DataKey::Predecessor(current)) {
+            lineage.push_front(previous);
+            current = previous;
+        }
+        
+        lineage.push_back(circle_id);
+        current = circle_id;
+        while let Some(next) = env.storage().instance().get::<DataKey, u32>(&DataKey::Successor(current)) {
+            lineage.push_back(next);
+            current = next;
+        }
+        
+        lineage
+    }
+    
     /// Join an existing circle with the correct password
     pub fn join_circle(env: Env, joiner: Address, circle_id: u32, password: String) -> Result<bool, Error> {
         joiner.require_auth();
@@ -216,6 +835,10 @@ impl Contract {
             return Err(Error::CircleBetrayed);
         }
         
+        if env.storage().instance().has(&DataKey::CircleClosed(circle_id)) {
+            return Err(Error::CircleClosed);
+        }
+        
         // Prevent the owner/creator from joining their own circle
         if circle.creator == joiner {
             return Err(Error::CannotJoinOwnCircle);
@@ -244,6 +867,19 @@ impl Contract {
             }
         }
         
+        // Circles may turn away players whose reputation is too low
+        if let Some(min_trust) = env.storage().instance().get::<DataKey, i32>(&DataKey::MinTrust(circle_id)) {
+            if Self::get_trust_score(env.clone(), joiner.clone()) < min_trust {
+                return Err(Error::TrustTooLow);
+            }
+        }
+        
+        // Escrow the circle's join bond before admitting the joiner
+        Self::post_join_bond(&env, circle_id, &joiner)?;
+        
+        // Harvests are pulled from members with transfer_from, so they approve the contract like creators do
+        let _ = Self::safe_approve_kale(&env, &joiner);
+        
         // Add joiner to this circle's member list
         members.push_back(joiner.clone());
         env.storage().instance().set(&DataKey::CircleMembers(circle_id), &members);
@@ -256,7 +892,17 @@ impl Contract {
         current_circles.push_back(circle_id);
         env.storage().instance().set(&DataKey::WalletCircle(joiner.clone()), &current_circles);
         
-        // Update scoreboard stats for joiner
+        // Remember when the joiner arrived for tenure-weighted splits
+        env.storage().instance().set(&DataKey::MemberJoinedAt(circle_id, joiner.clone()), &env.ledger().sequence());
+        
+        // Update scoreboard stats for joiner; leaving and rejoining doesn't earn the join points again
+        let awarded_key = DataKey::JoinTrustAwarded(circle_id, joiner.clone());
+        if !env.storage().persistent().has(&awarded_key) {
+            let params = Self::load_trust_params(&env);
+            Self::adjust_trust(&env, &joiner, params.join_points);
+            env.storage().persistent().set(&awarded_key, &true);
+        }
+        Self::extend_persistent(&env, &awarded_key);
         Self::update_player_stats_joined(&env, &joiner);
         
         Ok(true)
@@ -273,314 +919,2194 @@ impl Contract {
             None => return Err(Error::CircleDoesNotExist),
         };
         
-        // Check if already betrayed
-        if circle.betrayed {
-            return Err(Error::CircleBetrayed);
-        }
+        // Check if already betrayed, unless the betrayal window is still open
+        Self::check_can_betray(&env, circle_id, &circle, &betrayer)?;
         
         // Prevent the owner/creator from betraying their own circle
         if circle.creator == betrayer {
             return Err(Error::NotOwner);
         }
         
+        // Sealed circles only accept betrayals through commit and reveal
+        if env.storage().instance().has(&DataKey::SealedBetrayal(circle_id)) {
+            return Err(Error::SealedBetrayalOnly);
+        }
+        
         // Verify password - clone the password_hash to avoid move
         Self::check_password(&env, password, circle.password_hash.clone())?;
+        
+        // Only members who have been around long enough may betray
+        Self::check_betrayal_tenure(&env, circle_id, &betrayer)?;
+        
+        Self::post_betrayal_deposit(&env, circle_id, &betrayer)?;
 
-        // Mark circle as betrayed and record the betrayer
-        circle.betrayed = true;
-        circle.betrayer = Some(betrayer.clone());
-        env.storage().instance().set(&DataKey::Circle(circle_id), &circle);
-
-        // Update scoreboard stats
-        Self::update_player_stats_betrayed(&env, &betrayer);
-        Self::update_player_stats_was_betrayed(&env, &circle.creator);
+        Self::apply_betrayal(&env, circle_id, &mut circle, &betrayer);
 
         Ok(true)
     }
-
-    /// Set a new password for a circle (only creator can do this)
-    pub fn set_password(env: Env, caller: Address, circle_id: u32, password_hash: BytesN<32>) -> Result<bool, Error> {
+    
+    /// Leave a circle; a member who leaves is no longer harvested or exposed to a pending betrayal
+    pub fn leave_circle(env: Env, member: Address, circle_id: u32) -> Result<bool, Error> {
+        member.require_auth();
+        
+        let mut circle: Circle = match env.storage().instance().get(&DataKey::Circle(circle_id)) {
+            Some(c) => c,
+            None => return Err(Error::CircleDoesNotExist),
+        };
+        
+        if !Self::is_circle_member(&env, circle_id, &member) {
+            return Err(Error::NotAMember);
+        }
+        
+        Self::remove_member(&env, circle_id, &mut circle, &member);
+        Self::refund_join_bond(&env, circle_id, &member);
+        
+        Ok(true)
+    }
+    
+    /// Require new members to post a refundable KALE bond (only creator can do this)
+    pub fn set_join_bond(env: Env, caller: Address, circle_id: u32, amount: i128) -> Result<bool, Error> {
+        caller.require_auth();
+        
+        let circle: Circle = match env.storage().instance().get(&DataKey::Circle(circle_id)) {
+            Some(c) => c,
+            None => return Err(Error::CircleDoesNotExist),
+        };
+        
+        if circle.creator != caller {
+            return Err(Error::NotOwner);
+        }
+        
+        if amount < 0 {
+            return Err(Error::InvalidAmount);
+        }
+        
+        // Only affects members joining from now on
+        env.storage().instance().set(&DataKey::JoinBond(circle_id), &amount);
+        
+        Ok(true)
+    }
+    
+    /// Get the bond a circle requires from new members
+    pub fn get_join_bond(env: Env, circle_id: u32) -> i128 {
+        env.storage().instance().get(&DataKey::JoinBond(circle_id)).unwrap_or(0)
+    }
+    
+    /// Get the bond held for a member of a circle
+    pub fn get_member_bond(env: Env, circle_id: u32, member: Address) -> i128 {
+        env.storage().instance().get(&DataKey::MemberBond(circle_id, member)).unwrap_or(0)
+    }
+    
+    /// Close a circle for good, refunding every member's bond (only creator can do this)
+    pub fn close_circle(env: Env, caller: Address, circle_id: u32) -> Result<bool, Error> {
+        caller.require_auth();
+        
+        let circle: Circle = match env.storage().instance().get(&DataKey::Circle(circle_id)) {
+            Some(c) => c,
+            None => return Err(Error::CircleDoesNotExist),
+        };
+        
+        if circle.creator != caller {
+            return Err(Error::NotOwner);
+        }
+        
+        if env.storage().instance().has(&DataKey::CircleClosed(circle_id)) {
+            return Err(Error::CircleClosed);
+        }
+        
+        Self::mark_closed(&env, circle_id);
+        
+        Ok(true)
+    }
+    
+    /// Check whether a circle was closed by its creator
+    pub fn is_circle_closed(env: Env, circle_id: u32) -> bool {
+        env.storage().instance().has(&DataKey::CircleClosed(circle_id))
+    }
+    
+    /// Remove a member from a circle (only creator can do this)
+    pub fn kick_member(env: Env, caller: Address, circle_id: u32, member: Address) -> Result<bool, Error> {
         caller.require_auth();
         
-        // Get the circle
         let mut circle: Circle = match env.storage().instance().get(&DataKey::Circle(circle_id)) {
             Some(c) => c,
             None => return Err(Error::CircleDoesNotExist),
         };
         
-        // Check if caller is the creator
         if circle.creator != caller {
             return Err(Error::NotOwner);
         }
         
-        // Check if circle is betrayed
+        if !Self::is_circle_member(&env, circle_id, &member) {
+            return Err(Error::NotAMember);
+        }
+        
+        Self::remove_member(&env, circle_id, &mut circle, &member);
+        Self::refund_join_bond(&env, circle_id, &member);
+        
+        Ok(true)
+    }
+    
+    /// Make betrayals in a circle hidden until revealed after a delay, or turn it off (only creator can do this)
+    pub fn set_sealed_betrayal(env: Env, caller: Address, circle_id: u32, enabled: bool, reveal_delay: u32) -> Result<bool, Error> {
+        caller.require_auth();
+        
+        let circle: Circle = match env.storage().instance().get(&DataKey::Circle(circle_id)) {
+            Some(c) => c,
+            None => return Err(Error::CircleDoesNotExist),
+        };
+        
+        if circle.creator != caller {
+            return Err(Error::NotOwner);
+        }
+        
         if circle.betrayed {
             return Err(Error::CircleBetrayed);
         }
         
-        // Update password hash
-        circle.password_hash = password_hash;
-        env.storage().instance().set(&DataKey::Circle(circle_id), &circle);
+        if enabled {
+            env.storage().instance().set(&DataKey::SealedBetrayal(circle_id), &reveal_delay);
+        } else {
+            env.storage().instance().remove(&DataKey::SealedBetrayal(circle_id));
+        }
         
         Ok(true)
     }
-
-    /// Robust harvest and distribution with comprehensive error handling and earnings tracking
-    pub fn harvest_and_distribute_all(env: Env, caller: Address, index: u32) -> HarvestResult {
+    
+    /// Get the reveal delay of a sealed circle, or None if betrayals are open
+    pub fn get_sealed_betrayal(env: Env, circle_id: u32) -> Option<u32> {
+        env.storage().instance().get(&DataKey::SealedBetrayal(circle_id))
+    }
+    
+    /// Commit to a hidden betrayal with sha256(salt ‖ address XDR)
+    pub fn commit_betrayal(env: Env, member: Address, circle_id: u32, commitment: BytesN<32>) -> Result<u32, Error> {
+        member.require_auth();
+        
+        let circle: Circle = match env.storage().instance().get(&DataKey::Circle(circle_id)) {
+            Some(c) => c,
+            None => return Err(Error::CircleDoesNotExist),
+        };
+        
+        Self::check_can_betray(&env, circle_id, &circle, &member)?;
+        
+        let reveal_delay: u32 = match env.storage().instance().get(&DataKey::SealedBetrayal(circle_id)) {
+            Some(d) => d,
+            None => return Err(Error::NotSealedCircle),
+        };
+        
+        if !Self::is_circle_member(&env, circle_id, &member) {
+            return Err(Error::NotAMember);
+        }
+        
+        let committed_at = env.ledger().sequence();
+        let record = BetrayalCommitment {
+            commitment,
+            committed_at,
+        };
+        env.storage().instance().set(&DataKey::BetrayalCommitment(circle_id, member), &record);
+        
+        Ok(committed_at.saturating_add(reveal_delay))
+    }
+    
+    /// Reveal a committed betrayal once the delay has passed; it takes effect only now
+    pub fn reveal_betrayal(env: Env, member: Address, circle_id: u32, salt: BytesN<32>) -> Result<bool, Error> {
+        member.require_auth();
+        
+        let mut circle: Circle = match env.storage().instance().get(&DataKey::Circle(circle_id)) {
+            Some(c) => c,
+            None => return Err(Error::CircleDoesNotExist),
+        };
+        
+        Self::check_can_betray(&env, circle_id, &circle, &member)?;
+        
+        let reveal_delay: u32 = match env.storage().instance().get(&DataKey::SealedBetrayal(circle_id)) {
+            Some(d) => d,
+            None => return Err(Error::NotSealedCircle),
+        };
+        
+        let record: BetrayalCommitment = match env.storage().instance().get(&DataKey::BetrayalCommitment(circle_id, member.clone())) {
+            Some(r) => r,
+            None => return Err(Error::NoCommitment),
+        };
+        
+        if env.ledger().sequence() < record.committed_at.saturating_add(reveal_delay) {
+            return Err(Error::RevealTooEarly);
+        }
+        
+        if Self::betrayal_commitment(&env, &salt, &member) != record.commitment {
+            return Err(Error::CommitmentMismatch);
+        }
+        
+        // A traitor who was kicked or left in the meantime can no longer betray
+        Self::check_betrayal_tenure(&env, circle_id, &member)?;
+        Self::post_betrayal_deposit(&env, circle_id, &member)?;
+        
+        env.storage().instance().remove(&DataKey::BetrayalCommitment(circle_id, member.clone()));
+        Self::apply_betrayal(&env, circle_id, &mut circle, &member);
+        
+        Ok(true)
+    }
+    
+    /// Make betrayal a collective act requiring a quorum of member votes (only creator can do this, before any betrayal)
+    pub fn set_mutiny_mode(env: Env, caller: Address, circle_id: u32, config: Option<MutinyConfig>) -> Result<bool, Error> {
         caller.require_auth();
         
-        let mut total_distributed = 0i128;
-        let mut successful_circles = 0u32;
-        let mut failed_harvests = 0u32;
+        let circle: Circle = match env.storage().instance().get(&DataKey::Circle(circle_id)) {
+            Some(c) => c,
+            None => return Err(Error::CircleDoesNotExist),
+        };
         
-        // Get all circle IDs safely
-        let all_circle_ids: Vec<u32> = env.storage().instance().get(&DataKey::AllCircleIds).unwrap_or(vec![&env]);
+        if circle.creator != caller {
+            return Err(Error::NotOwner);
+        }
         
-        // Process each circle with individual error handling
-        for circle_id in all_circle_ids.iter() {
-            let circle_id_val = circle_id.clone();
-            
-            // Safely get circle data
-            let circle_opt: Option<Circle> = env.storage().instance().get(&DataKey::Circle(circle_id_val));
-            
-            let circle = match circle_opt {
-                Some(c) => c,
-                None => {
-                    failed_harvests += 1;
-                    continue; // Skip this circle if it doesn't exist
+        if circle.betrayed {
+            return Err(Error::CircleBetrayed);
+        }
+        
+        match config {
+            Some(config) => {
+                if config.quorum_bps == 0 || config.quorum_bps > 10_000 {
+                    return Err(Error::InvalidBasisPoints);
+                }
+                env.storage().instance().set(&DataKey::MutinyConfig(circle_id), &config);
+            },
+            None => env.storage().instance().remove(&DataKey::MutinyConfig(circle_id)),
+        }
+        
+        // Changing the rules discards any vote in progress
+        env.storage().instance().remove(&DataKey::MutinyVotes(circle_id));
+        
+        Ok(true)
+    }
+    
+    /// Get a circle's mutiny settings, or None if betrayal is an individual act
+    pub fn get_mutiny_config(env: Env, circle_id: u32) -> Option<MutinyConfig> {
+        env.storage().instance().get(&DataKey::MutinyConfig(circle_id))
+    }
+    
+    /// Vote to betray a mutiny circle; the betrayal happens once the quorum is reached.
+    /// Each voter posts the circle's betrayal deposit, refunded if the vote expires or they leave before quorum.
+    pub fn vote_betray(env: Env, member: Address, circle_id: u32) -> Result<bool, Error> {
+        member.require_auth();
+        
+        let mut circle: Circle = match env.storage().instance().get(&DataKey::Circle(circle_id)) {
+            Some(c) => c,
+            None => return Err(Error::CircleDoesNotExist),
+        };
+        
+        if circle.betrayed {
+            return Err(Error::CircleBetrayed);
+        }
+        
+        if env.storage().instance().has(&DataKey::CircleClosed(circle_id)) {
+            return Err(Error::CircleClosed);
+        }
+        
+        let config: MutinyConfig = match env.storage().instance().get(&DataKey::MutinyConfig(circle_id)) {
+            Some(c) => c,
+            None => return Err(Error::NotMutinyCircle),
+        };
+        
+        // Only members who have been around long enough may vote
+        Self::check_betrayal_tenure(&env, circle_id, &member)?;
+        
+        let status = Self::mutiny_status(&env, circle_id, &config);
+        let stored: Vec<Address> = match env.storage().instance().get::<DataKey, MutinyVotes>(&DataKey::MutinyVotes(circle_id)) {
+            Some(votes) => votes.voters,
+            None => vec![&env],
+        };
+        let mut voters = if status.active { status.voters } else { vec![&env] };
+        let opened_at = if status.active { status.opened_at } else { env.ledger().sequence() };
+        
+        if voters.contains(&member) {
+            return Err(Error::AlreadyVoted);
+        }
+        
+        // Voters of an expired round get their deposits back before a new one opens
+        if !status.active {
+            for voter in stored.iter() {
+                Self::refund_betrayal_deposit(&env, circle_id, &voter)?;
+            }
+        }
+        
+        // Every mutineer stakes the same deposit as a lone betrayer
+        Self::post_betrayal_deposit(&env, circle_id, &member)?;
+        voters.push_back(member.clone());
+        
+        env.events().publish((symbol_short!("mutiny"), circle_id), member);
+        
+        if voters.len() < status.votes_needed {
+            env.storage().instance().set(&DataKey::MutinyVotes(circle_id), &MutinyVotes { voters, opened_at });
+            return Ok(false);
+        }
+        
+        // Voters who left the circle before quorum aren't mutineers and get their deposits back
+        if status.active {
+            for voter in stored.iter() {
+                if !voters.contains(&voter) {
+                    Self::refund_betrayal_deposit(&env, circle_id, &voter)?;
                 }
-            };
-            
-            // Skip if no members
-            if circle.member_count == 0 {
-                continue;
             }
+        }
+        
+        env.storage().instance().remove(&DataKey::MutinyVotes(circle_id));
+        Self::apply_mutiny(&env, circle_id, &mut circle, &voters);
+        
+        Ok(true)
+    }
+    
+    /// Get the votes, quorum progress and expiry of a circle's mutiny
+    pub fn get_mutiny_status(env: Env, circle_id: u32) -> Option<MutinyStatus> {
+        let config: MutinyConfig = env.storage().instance().get(&DataKey::MutinyConfig(circle_id))?;
+        Some(Self::mutiny_status(&env, circle_id, &config))
+    }
+    
+    /// Let victims retaliate against betrayers for a while (only creator can do this, before any betrayal)
+    pub fn set_revenge_terms(env: Env, caller: Address, circle_id: u32, terms: Option<RevengeTerms>) -> Result<bool, Error> {
+        caller.require_auth();
+        
+        let circle: Circle = match env.storage().instance().get(&DataKey::Circle(circle_id)) {
+            Some(c) => c,
+            None => return Err(Error::CircleDoesNotExist),
+        };
+        
+        if circle.creator != caller {
+            return Err(Error::NotOwner);
+        }
+        
+        if circle.betrayed {
+            return Err(Error::CircleBetrayed);
+        }
+        
+        match terms {
+            Some(terms) => {
+                if terms.clawback_bps > 10_000 {
+                    return Err(Error::InvalidBasisPoints);
+                }
+                if terms.deposit < 0 {
+                    return Err(Error::InvalidAmount);
+                }
+                env.storage().instance().set(&DataKey::RevengeTerms(circle_id), &terms);
+            },
+            None => env.storage().instance().remove(&DataKey::RevengeTerms(circle_id)),
+        }
+        
+        Ok(true)
+    }
+    
+    /// Get a circle's revenge terms, or None if betrayals go unanswered
+    pub fn get_revenge_terms(env: Env, circle_id: u32) -> Option<RevengeTerms> {
+        env.storage().instance().get(&DataKey::RevengeTerms(circle_id))
+    }
+    
+    /// Claw back part of the betrayers' held rewards and slash their deposits (creator or loyal members, within the revenge window)
+    pub fn retaliate(env: Env, caller: Address, circle_id: u32) -> Result<i128, Error> {
+        caller.require_auth();
+        
+        let circle: Circle = match env.storage().instance().get(&DataKey::Circle(circle_id)) {
+            Some(c) => c,
+            None => return Err(Error::CircleDoesNotExist),
+        };
+        
+        let terms: RevengeTerms = match env.storage().instance().get(&DataKey::RevengeTerms(circle_id)) {
+            Some(t) => t,
+            None => return Err(Error::NoRevengeTerms),
+        };
+        
+        if env.storage().instance().has(&DataKey::Retaliated(circle_id)) {
+            return Err(Error::AlreadyRetaliated);
+        }
+        
+        if !Self::revenge_window_open(&env, circle_id, &circle) {
+            return Err(Error::RevengeWindowClosed);
+        }
+        
+        let victims = Self::betrayal_victims(&env, circle_id, &circle);
+        if !victims.contains(&caller) {
+            return Err(Error::NotAMember);
+        }
+        
+        // Take the clawback share of every betrayer's held rewards and their whole deposit
+        let mut recovered = 0i128;
+        for betrayer in Self::load_betrayers(&env, circle_id).iter() {
+            let held_key = DataKey::HeldBetrayalRewards(circle_id, betrayer.clone());
+            let held: i128 = env.storage().instance().get(&held_key).unwrap_or(0);
+            let clawed = held * terms.clawback_bps as i128 / 10_000;
+            env.storage().instance().set(&held_key, &(held - clawed));
             
-            // Get all members of this circle safely
-            let members: Vec<Address> = env.storage().instance().get(&DataKey::CircleMembers(circle_id_val)).unwrap_or(vec![&env]);
+            let deposit: i128 = env.storage().instance().get(&DataKey::BetrayalDeposit(circle_id, betrayer.clone())).unwrap_or(0);
+            env.storage().instance().remove(&DataKey::BetrayalDeposit(circle_id, betrayer.clone()));
             
-            if members.is_empty() {
-                continue;
+            let lost = clawed + deposit;
+            if lost > 0 {
+                Self::update_player_earnings_lost_to_retaliation(&env, &betrayer, lost);
             }
+            Self::update_player_stats_retaliated_against(&env, &betrayer);
+            recovered += lost;
+        }
+        
+        env.storage().instance().set(&DataKey::Retaliated(circle_id), &true);
+        Self::record_treasury_flow(&env, DataKey::TotalRevengeHeld, -recovered);
+        Self::update_player_stats_retaliation(&env, &caller);
+        
+        if recovered > 0 {
+            let kale_client = Self::get_kale_client(&env)?;
             
-            // Process this circle's harvest with error isolation
-            match Self::process_circle_harvest(&env, &circle, &members, circle_id_val, index) {
-                Ok(distributed_amount) => {
-                    total_distributed += distributed_amount;
-                    if distributed_amount > 0 {
-                        successful_circles += 1;
-                        // Update circle earnings tracking
-                        Self::update_circle_earnings(&env, circle_id_val, distributed_amount);
-                    }
-                },
-                Err(_) => {
-                    failed_harvests += 1;
-                    // Continue processing other circles even if this one fails
+            // Share what was recovered equally among the victims, the caller getting the remainder
+            let share = recovered / victims.len() as i128;
+            let remainder = recovered - share * victims.len() as i128;
+            for victim in victims.iter() {
+                let amount = if victim == caller { share + remainder } else { share };
+                if amount <= 0 {
+                    continue;
+                }
+                if Self::safe_transfer_from_contract(&env, &kale_client, &victim, amount).is_err() {
+                    Self::credit_pending(&env, &victim, amount);
                 }
+                Self::update_player_earnings_retaliation(&env, &victim, amount);
             }
+            
+            Self::check_pending_invariant(&env, &kale_client);
         }
         
-        // Update global total earnings
-        if total_distributed > 0 {
-            let current_total: i128 = env.storage().instance().get(&DataKey::TotalKaleEarned).unwrap_or(0);
-            env.storage().instance().set(&DataKey::TotalKaleEarned, &(current_total + total_distributed));
+        env.events().publish((symbol_short!("revenge"), circle_id), (caller, recovered));
+        
+        Ok(recovered)
+    }
+    
+    /// Release a betrayer's held rewards and deposit once the revenge window has passed.
+    /// Deposits of mutiny votes that never passed are refunded by the vote itself, not here.
+    pub fn release_betrayal_escrow(env: Env, betrayer: Address, circle_id: u32) -> Result<i128, Error> {
+        betrayer.require_auth();
+        
+        let circle: Circle = match env.storage().instance().get(&DataKey::Circle(circle_id)) {
+            Some(c) => c,
+            None => return Err(Error::CircleDoesNotExist),
+        };
+        
+        if !circle.betrayed {
+            return Err(Error::CircleNotBetrayed);
+        }
+        
+        if !Self::load_betrayers(&env, circle_id).contains(&betrayer) {
+            return Err(Error::NotABetrayer);
+        }
+        
+        if Self::revenge_window_open(&env, circle_id, &circle) && !env.storage().instance().has(&DataKey::Retaliated(circle_id)) {
+            return Err(Error::RevengeWindowOpen);
+        }
+        
+        let (held, deposit) = Self::get_betrayal_escrow(env.clone(), circle_id, betrayer.clone());
+        let amount = held + deposit;
+        if amount <= 0 {
+            return Err(Error::NothingToClaim);
+        }
+        
+        env.storage().instance().remove(&DataKey::HeldBetrayalRewards(circle_id, betrayer.clone()));
+        env.storage().instance().remove(&DataKey::BetrayalDeposit(circle_id, betrayer.clone()));
+        Self::record_treasury_flow(&env, DataKey::TotalRevengeHeld, -amount);
+        
+        let kale_client = Self::get_kale_client(&env)?;
+        if Self::safe_transfer_from_contract(&env, &kale_client, &betrayer, amount).is_err() {
+            Self::credit_pending(&env, &betrayer, amount);
+        }
+        
+        Ok(amount)
+    }
+    
+    /// Get a betrayer's rewards held for the revenge window and their posted deposit
+    pub fn get_betrayal_escrow(env: Env, circle_id: u32, betrayer: Address) -> (i128, i128) {
+        let held: i128 = env.storage().instance().get(&DataKey::HeldBetrayalRewards(circle_id, betrayer.clone())).unwrap_or(0);
+        let deposit: i128 = env.storage().instance().get(&DataKey::BetrayalDeposit(circle_id, betrayer)).unwrap_or(0);
+        (held, deposit)
+    }
+    
+    /// Check whether a circle's victims already retaliated
+    pub fn has_retaliated(env: Env, circle_id: u32) -> bool {
+        env.storage().instance().has(&DataKey::Retaliated(circle_id))
+    }
+    
+    /// Pay KALE back to the victims of a past betrayal, partly restoring the betrayer's trust score
+    pub fn pay_restitution(env: Env, betrayer: Address, circle_id: u32, amount: i128) -> Result<i128, Error> {
+        betrayer.require_auth();
+        
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        
+        let circle: Circle = match env.storage().instance().get(&DataKey::Circle(circle_id)) {
+            Some(c) => c,
+            None => return Err(Error::CircleDoesNotExist),
+        };
+        
+        if !Self::load_betrayers(&env, circle_id).contains(&betrayer) {
+            return Err(Error::NotABetrayer);
+        }
+        
+        let kale_client = Self::get_kale_client(&env)?;
+        Self::safe_transfer_to_contract(&env, &kale_client, &betrayer, amount)?;
+        
+        // Share the restitution equally among the victims, the creator getting the remainder
+        let victims = Self::betrayal_victims(&env, circle_id, &circle);
+        let share = amount / victims.len() as i128;
+        let remainder = amount - share * victims.len() as i128;
+        for victim in victims.iter() {
+            let paid = if victim == circle.creator { share + remainder } else { share };
+            if paid <= 0 {
+                continue;
+            }
+            if Self::safe_transfer_from_contract(&env, &kale_client, &victim, paid).is_err() {
+                Self::credit_pending(&env, &victim, paid);
+            }
+            Self::update_player_earnings_restitution(&env, &victim, paid);
+        }
+        Self::check_pending_invariant(&env, &kale_client);
+        
+        let key = DataKey::Restitution(circle_id, betrayer.clone());
+        let total: i128 = env.storage().instance().get(&key).unwrap_or(0);
+        env.storage().instance().set(&key, &(total + amount));
+        Self::restore_trust(&env, circle_id, &betrayer, amount);
+        Self::update_player_stats_restitution(&env, &betrayer, amount);
+        
+        env.events().publish((symbol_short!("restitute"), circle_id), (betrayer, amount));
+        
+        Ok(total + amount)
+    }
+    
+    /// Get how much a betrayer has paid back to a circle's victims
+    pub fn get_restitution(env: Env, circle_id: u32, betrayer: Address) -> i128 {
+        env.storage().instance().get(&DataKey::Restitution(circle_id, betrayer)).unwrap_or(0)
+    }
+    
+    /// Get a player's trust score as shown on the scoreboard
+    pub fn get_trust_score(env: Env, player: Address) -> i32 {
+        let record = Self::current_trust(&env, &player, &Self::load_trust_params(&env));
+        (record.score / TRUST_SCALE).clamp(i32::MIN as i128, i32::MAX as i128) as i32
+    }
+    
+    /// Get a player's trust record decayed to the current ledger
+    pub fn get_trust_record(env: Env, player: Address) -> TrustRecord {
+        Self::current_trust(&env, &player, &Self::load_trust_params(&env))
+    }
+    
+    /// Replace the trust model's weights (admin only); the version is bumped so old records get re-seeded
+    pub fn set_trust_params(env: Env, params: TrustParams) -> Result<u32, Error> {
+        Self::require_admin(&env)?;
+        
+        if params.half_life_ledgers == 0
+            || params.join_points < 0
+            || params.betrayal_points < 0
+            || params.betrayal_points_per_unit < 0
+            || params.tenure_points < 0
+            || params.restitution_points_per_unit < 0 {
+            return Err(Error::InvalidTrustParams);
+        }
+        
+        let version = Self::load_trust_params(&env).version + 1;
+        env.storage().instance().set(&DataKey::TrustParams, &TrustParams { version, ..params });
+        
+        Ok(version)
+    }
+    
+    /// Get the trust model's current weights
+    pub fn get_trust_params(env: Env) -> TrustParams {
+        Self::load_trust_params(&env)
+    }
+    
+    /// Require a minimum trust score to join a circle, or None to let anyone in (only creator can do this)
+    pub fn set_min_trust(env: Env, caller: Address, circle_id: u32, min_trust: Option<i32>) -> Result<bool, Error> {
+        caller.require_auth();
+        
+        let circle: Circle = match env.storage().instance().get(&DataKey::Circle(circle_id)) {
+            Some(c) => c,
+            None => return Err(Error::CircleDoesNotExist),
+        };
+        
+        if circle.creator != caller {
+            return Err(Error::NotOwner);
+        }
+        
+        match min_trust {
+            Some(min_trust) => env.storage().instance().set(&DataKey::MinTrust(circle_id), &min_trust),
+            None => env.storage().instance().remove(&DataKey::MinTrust(circle_id)),
+        }
+        
+        Ok(true)
+    }
+    
+    /// Get the trust score a circle requires from new members
+    pub fn get_min_trust(env: Env, circle_id: u32) -> Option<i32> {
+        env.storage().instance().get(&DataKey::MinTrust(circle_id))
+    }
+    
+    /// Set how many ledgers a member must have been in a circle before betraying it (admin only)
+    pub fn set_min_betrayal_tenure(env: Env, ledgers: u32) -> Result<(), Error> {
+        Self::require_admin(&env)?;
+        env.storage().instance().set(&DataKey::MinBetrayalTenure, &ledgers);
+        Ok(())
+    }
+    
+    /// Get the minimum membership age required to betray
+    pub fn get_min_betrayal_tenure(env: Env) -> u32 {
+        env.storage().instance().get(&DataKey::MinBetrayalTenure).unwrap_or(DEFAULT_MIN_BETRAYAL_TENURE)
+    }
+    
+    /// Choose what a betrayer takes and what happens to the circle afterwards (only creator can do this, before any betrayal)
+    pub fn set_betrayal_terms(env: Env, caller: Address, circle_id: u32, terms: BetrayalTerms) -> Result<bool, Error> {
+        caller.require_auth();
+        
+        let circle: Circle = match env.storage().instance().get(&DataKey::Circle(circle_id)) {
+            Some(c) => c,
+            None => return Err(Error::CircleDoesNotExist),
+        };
+        
+        if circle.creator != caller {
+            return Err(Error::NotOwner);
+        }
+        
+        if circle.betrayed {
+            return Err(Error::CircleBetrayed);
+        }
+        
+        if let BetrayalRule::TakeRecentHarvests(bps) = terms.rule {
+            if bps > 10_000 {
+                return Err(Error::InvalidBasisPoints);
+            }
+        }
+        
+        env.storage().instance().set(&DataKey::BetrayalTerms(circle_id), &terms);
+        
+        Ok(true)
+    }
+    
+    /// Get the betrayal terms that apply to a circle
+    pub fn get_betrayal_terms(env: Env, circle_id: u32) -> BetrayalTerms {
+        Self::load_betrayal_terms(&env, circle_id)
+    }
+    
+    /// Get the KALE a betrayer is still owed from a circle's future harvests
+    pub fn get_betrayal_debt(env: Env, circle_id: u32) -> i128 {
+        env.storage().instance().get(&DataKey::BetrayalDebt(circle_id)).unwrap_or(0)
+    }
+    
+    /// Let other members join a betrayal within a window after the first one (only creator can do this, before any betrayal)
+    pub fn set_betrayal_window(env: Env, caller: Address, circle_id: u32, window: BetrayalWindow) -> Result<bool, Error> {
+        caller.require_auth();
+        
+        let circle: Circle = match env.storage().instance().get(&DataKey::Circle(circle_id)) {
+            Some(c) => c,
+            None => return Err(Error::CircleDoesNotExist),
+        };
+        
+        if circle.creator != caller {
+            return Err(Error::NotOwner);
+        }
+        
+        if circle.betrayed {
+            return Err(Error::CircleBetrayed);
+        }
+        
+        env.storage().instance().set(&DataKey::BetrayalWindow(circle_id), &window);
+        
+        Ok(true)
+    }
+    
+    /// Get a circle's betrayal window; a zero-length window means the first betrayer wins outright
+    pub fn get_betrayal_window(env: Env, circle_id: u32) -> BetrayalWindow {
+        Self::load_betrayal_window(&env, circle_id)
+    }
+    
+    /// Split the betrayer payouts held during a betrayal window among the betrayers once it has closed.
+    /// Under PrisonersDilemma they are voided instead if every member defected; the KALE stays unclaimed.
+    pub fn settle_betrayal_window(env: Env, circle_id: u32) -> Result<i128, Error> {
+        if !env.storage().instance().has(&DataKey::Circle(circle_id)) {
+            return Err(Error::CircleDoesNotExist);
+        }
+        
+        if Self::betrayal_window_open(&env, circle_id) {
+            return Err(Error::BetrayalWindowOpen);
+        }
+        
+        let held = Self::take_window_hold(&env, circle_id);
+        if held <= 0 {
+            return Err(Error::NothingToClaim);
+        }
+        
+        let window = Self::load_betrayal_window(&env, circle_id);
+        let members: Vec<Address> = env.storage().instance().get(&DataKey::CircleMembers(circle_id)).unwrap_or(vec![&env]);
+        if window.rule == MultiBetrayalRule::PrisonersDilemma && Self::loyal_members(&env, circle_id, &members).is_empty() {
+            Self::return_voided_spoils(&env, circle_id, held);
+            return Ok(0);
+        }
+        
+        let kale_client = Self::get_kale_client(&env)?;
+        Self::pay_betrayers(&env, &kale_client, circle_id, held);
+        Self::check_pending_invariant(&env, &kale_client);
+        
+        Ok(held)
+    }
+    
+    /// Get the betrayer payouts a circle is holding until its betrayal window closes
+    pub fn get_window_held_rewards(env: Env, circle_id: u32) -> i128 {
+        env.storage().instance().get(&DataKey::WindowHeldRewards(circle_id)).unwrap_or(0)
+    }
+    
+    /// Get everyone who betrayed a circle
+    pub fn get_circle_betrayers(env: Env, circle_id: u32) -> Vec<Address> {
+        Self::load_betrayers(&env, circle_id)
+    }
+    
+    /// Check whether a circle stopped harvesting after its betrayal was settled
+    pub fn is_circle_frozen(env: Env, circle_id: u32) -> bool {
+        env.storage().instance().has(&DataKey::CircleFrozen(circle_id))
+    }
+
+    /// Set a new password for a circle (only creator can do this)
+    pub fn set_password(env: Env, caller: Address, circle_id: u32, password_hash: BytesN<32>) -> Result<bool, Error> {
+        caller.require_auth();
+        
+        // Get the circle
+        let mut circle: Circle = match env.storage().instance().get(&DataKey::Circle(circle_id)) {
+            Some(c) => c,
+            None => return Err(Error::CircleDoesNotExist),
+        };
+        
+        // Check if caller is the creator
+        if circle.creator != caller {
+            return Err(Error::NotOwner);
+        }
+        
+        // Check if circle is betrayed
+        if circle.betrayed {
+            return Err(Error::CircleBetrayed);
+        }
+        
+        // Update password hash
+        circle.password_hash = password_hash;
+        env.storage().instance().set(&DataKey::Circle(circle_id), &circle);
+        
+        Ok(true)
+    }
+
+    /// Queue a new distribution policy for a circle (only creator can do this)
+    pub fn queue_policy_change(env: Env, caller: Address, circle_id: u32, policy: DistributionPolicy) -> Result<u32, Error> {
+        caller.require_auth();
+        
+        let circle: Circle = match env.storage().instance().get(&DataKey::Circle(circle_id)) {
+            Some(c) => c,
+            None => return Err(Error::CircleDoesNotExist),
+        };
+        
+        if circle.creator != caller {
+            return Err(Error::NotOwner);
+        }
+        
+        if circle.betrayed {
+            return Err(Error::CircleBetrayed);
+        }
+        
+        Self::validate_policy(&policy)?;
+        
+        // Members get the timelock to react before the new split applies
+        let effective_ledger = env.ledger().sequence().saturating_add(POLICY_TIMELOCK_LEDGERS);
+        let pending = PendingPolicy {
+            policy,
+            effective_ledger,
+        };
+        env.storage().instance().set(&DataKey::PendingPolicy(circle_id), &pending);
+        
+        Ok(effective_ledger)
+    }
+    
+    /// Apply a queued distribution policy once its timelock has passed (only creator can do this)
+    pub fn apply_policy_change(env: Env, caller: Address, circle_id: u32) -> Result<bool, Error> {
+        caller.require_auth();
+        
+        let mut circle: Circle = match env.storage().instance().get(&DataKey::Circle(circle_id)) {
+            Some(c) => c,
+            None => return Err(Error::CircleDoesNotExist),
+        };
+        
+        if circle.creator != caller {
+            return Err(Error::NotOwner);
+        }
+        
+        if circle.betrayed {
+            return Err(Error::CircleBetrayed);
+        }
+        
+        let pending: PendingPolicy = match env.storage().instance().get(&DataKey::PendingPolicy(circle_id)) {
+            Some(p) => p,
+            None => return Err(Error::NoPendingPolicy),
+        };
+        
+        if env.ledger().sequence() < pending.effective_ledger {
+            return Err(Error::PolicyTimelocked);
+        }
+        
+        circle.policy = pending.policy;
+        env.storage().instance().set(&DataKey::Circle(circle_id), &circle);
+        env.storage().instance().remove(&DataKey::PendingPolicy(circle_id));
+        
+        Ok(true)
+    }
+    
+    /// Get the policy change waiting on a circle's timelock, if any
+    pub fn get_pending_policy(env: Env, circle_id: u32) -> Option<PendingPolicy> {
+        env.storage().instance().get(&DataKey::PendingPolicy(circle_id))
+    }
+    
+    /// Choose how a circle's harvest remainder is handled (only creator can do this)
+    pub fn set_remainder_strategy(env: Env, caller: Address, circle_id: u32, strategy: RemainderStrategy) -> Result<bool, Error> {
+        caller.require_auth();
+        
+        let circle: Circle = match env.storage().instance().get(&DataKey::Circle(circle_id)) {
+            Some(c) => c,
+            None => return Err(Error::CircleDoesNotExist),
+        };
+        
+        if circle.creator != caller {
+            return Err(Error::NotOwner);
+        }
+        
+        env.storage().instance().set(&DataKey::RemainderStrategy(circle_id), &strategy);
+        
+        Ok(true)
+    }
+    
+    /// Get the remainder strategy of a circle
+    pub fn get_remainder_strategy(env: Env, circle_id: u32) -> RemainderStrategy {
+        env.storage().instance().get(&DataKey::RemainderStrategy(circle_id)).unwrap_or(RemainderStrategy::RotateByIndex)
+    }
+    
+    /// Get the dust a circle is carrying into its next harvest
+    pub fn get_carried_dust(env: Env, circle_id: u32) -> i128 {
+        env.storage().instance().get(&DataKey::CarriedDust(circle_id)).unwrap_or(0)
+    }
+
+    /// Robust harvest and distribution with comprehensive error handling and earnings tracking
+    pub fn harvest_and_distribute_all(env: Env, caller: Address, index: u32) -> HarvestResult {
+        caller.require_auth();
+        
+        let mut result = HarvestResult {
+            total_distributed: 0,
+            successful_circles: 0,
+            failed_harvests: 0,
+            circles: vec![&env],
+            keeper_tip: 0,
+        };
+        
+        // The keeper tip is capped across the whole call
+        let mut tip_budget = Self::load_keeper_config(&env).max_tip_per_call;
+        
+        // Get all circle IDs safely
+        let all_circle_ids: Vec<u32> = env.storage().instance().get(&DataKey::AllCircleIds).unwrap_or(vec![&env]);
+        let indices: Vec<u32> = vec![&env, index];
+        
+        // Process each circle with individual error handling
+        for circle_id in all_circle_ids.iter() {
+            Self::harvest_circle(&env, circle_id, &indices, &mut tip_budget, &mut result);
+        }
+        
+        Self::finish_harvest(&env, &caller, &result);
+        
+        result
+    }
+    
+    /// Harvest one circle for several block indices, pooling and distributing once; already processed indices are skipped
+    pub fn harvest_indices(env: Env, caller: Address, circle_id: u32, indices: Vec<u32>) -> HarvestResult {
+        caller.require_auth();
+        
+        let mut result = HarvestResult {
+            total_distributed: 0,
+            successful_circles: 0,
+            failed_harvests: 0,
+            circles: vec![&env],
+            keeper_tip: 0,
+        };
+        
+        let mut tip_budget = Self::load_keeper_config(&env).max_tip_per_call;
+        Self::harvest_circle(&env, circle_id, &indices, &mut tip_budget, &mut result);
+        Self::finish_harvest(&env, &caller, &result);
+        
+        result
+    }
+    
+    /// Allow or stop the circle from planting on a member's behalf
+    pub fn set_plant_consent(env: Env, member: Address, circle_id: u32, consent: bool) -> Result<bool, Error> {
+        member.require_auth();
+        
+        if !env.storage().instance().has(&DataKey::Circle(circle_id)) {
+            return Err(Error::CircleDoesNotExist);
+        }
+        
+        if !Self::is_circle_member(&env, circle_id, &member) {
+            return Err(Error::NotAMember);
+        }
+        
+        env.storage().instance().set(&DataKey::PlantConsent(circle_id, member), &consent);
+        
+        Ok(true)
+    }
+    
+    /// Plant the same stake for every consenting member of a circle in one transaction.
+    /// The mining contract burns the stake from each farmer and needs their own signature,
+    /// so every member to be planted must co-sign; members who didn't are reported as not planted.
+    pub fn plant_for_circle(env: Env, caller: Address, circle_id: u32, amount_per_member: i128) -> Result<Vec<PlantStatus>, Error> {
+        caller.require_auth();
+        
+        if amount_per_member <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        
+        if !env.storage().instance().has(&DataKey::Circle(circle_id)) {
+            return Err(Error::CircleDoesNotExist);
+        }
+        
+        let kale_client = Self::get_kale_client(&env)?;
+        let mining_client = Self::get_mining_client(&env)?;
+        let members: Vec<Address> = env.storage().instance().get(&DataKey::CircleMembers(circle_id)).unwrap_or(vec![&env]);
+        let round_ledger = env.ledger().sequence();
+        let mut statuses: Vec<PlantStatus> = vec![&env];
+        
+        for member in members.iter() {
+            let consented: bool = env.storage().instance().get(&DataKey::PlantConsent(circle_id, member.clone())).unwrap_or(false);
+            
+            // Members must have opted in and hold the stake; the plant itself fails without their signature
+            let mut planted = false;
+            if consented {
+                let balance = Self::safe_get_balance(&kale_client, &member).unwrap_or(0);
+                if balance >= amount_per_member {
+                    planted = mining_client.try_plant(&member, &amount_per_member).is_ok();
+                }
+            }
+            
+            let status = PlantStatus {
+                member: member.clone(),
+                consented,
+                amount: if planted { amount_per_member } else { 0 },
+                round_ledger,
+                planted,
+            };
+            env.storage().instance().set(&DataKey::PlantStatus(circle_id, member), &status);
+            statuses.push_back(status);
+        }
+        
+        env.storage().instance().set(&DataKey::LastPlantRound(circle_id), &round_ledger);
+        
+        Ok(statuses)
+    }
+    
+    /// Get every member's plant status for the circle's current planting round
+    pub fn get_plant_status(env: Env, circle_id: u32) -> Vec<PlantStatus> {
+        let members: Vec<Address> = env.storage().instance().get(&DataKey::CircleMembers(circle_id)).unwrap_or(vec![&env]);
+        let current_round: u32 = env.storage().instance().get(&DataKey::LastPlantRound(circle_id)).unwrap_or(0);
+        let mut statuses: Vec<PlantStatus> = vec![&env];
+        
+        for member in members.iter() {
+            let consented: bool = env.storage().instance().get(&DataKey::PlantConsent(circle_id, member.clone())).unwrap_or(false);
+            let stored: Option<PlantStatus> = env.storage().instance().get(&DataKey::PlantStatus(circle_id, member.clone()));
+            
+            // Statuses from earlier rounds don't count for the current one
+            let status = match stored {
+                Some(s) if s.round_ledger == current_round => PlantStatus { consented, ..s },
+                _ => PlantStatus {
+                    member,
+                    consented,
+                    amount: 0,
+                    round_ledger: current_round,
+                    planted: false,
+                },
+            };
+            statuses.push_back(status);
+        }
+        
+        statuses
+    }
+    
+    /// Set the share of each payout from a circle that is held back for the member to re-plant.
+    /// The mining contract burns the stake from the farmer and needs their signature, so the share
+    /// stays in the contract until the member plants it with plant_compounded.
+    pub fn set_compound_bps(env: Env, member: Address, circle_id: u32, bps: u32) -> Result<bool, Error> {
+        member.require_auth();
+        
+        if bps > 10_000 {
+            return Err(Error::InvalidBasisPoints);
+        }
+        
+        if !env.storage().instance().has(&DataKey::Circle(circle_id)) {
+            return Err(Error::CircleDoesNotExist);
+        }
+        
+        if !Self::is_circle_member(&env, circle_id, &member) {
+            return Err(Error::NotAMember);
+        }
+        
+        env.storage().instance().set(&DataKey::CompoundBps(circle_id, member), &bps);
+        
+        Ok(true)
+    }
+    
+    /// Get the share of payouts a member compounds in a circle
+    pub fn get_compound_bps(env: Env, circle_id: u32, member: Address) -> u32 {
+        env.storage().instance().get(&DataKey::CompoundBps(circle_id, member)).unwrap_or(0)
+    }
+    
+    /// Plant the payout shares held back for a member; a rejected plant leaves them held
+    pub fn plant_compounded(env: Env, member: Address) -> Result<i128, Error> {
+        member.require_auth();
+        
+        let key = DataKey::CompoundReserve(member.clone());
+        let amount: i128 = env.storage().instance().get(&key).unwrap_or(0);
+        if amount <= 0 {
+            return Err(Error::NothingToClaim);
+        }
+        
+        let kale_client = Self::get_kale_client(&env)?;
+        let mining_client = Self::get_mining_client(&env)?;
+        
+        // The stake is burned from the member, so hand it over first; failing the plant reverts the transfer too
+        Self::safe_transfer_from_contract(&env, &kale_client, &member, amount)?;
+        if mining_client.try_plant(&member, &amount).is_err() {
+            return Err(Error::PlantFailed);
+        }
+        
+        env.storage().instance().remove(&key);
+        Self::record_treasury_flow(&env, DataKey::TotalCompoundReserve, -amount);
+        Self::update_player_earnings_compounded(&env, &member, amount);
+        
+        Ok(amount)
+    }
+    
+    /// Get the payout shares held back for a member to plant
+    pub fn get_compound_reserve(env: Env, member: Address) -> i128 {
+        env.storage().instance().get(&DataKey::CompoundReserve(member)).unwrap_or(0)
+    }
+    
+    /// Relay proof-of-work submissions for several farmers; one failure doesn't abort the batch
+    pub fn submit_work_batch(env: Env, relayer: Address, entries: Vec<(Address, BytesN<32>, u64)>) -> Result<Vec<WorkResult>, Error> {
+        relayer.require_auth();
+        
+        let mining_client = Self::get_mining_client(&env)?;
+        let mut results: Vec<WorkResult> = vec![&env];
+        
+        for (farmer, hash, nonce) in entries.iter() {
+            let result = match mining_client.try_work(&farmer, &hash, &nonce) {
+                Ok(Ok(zeros)) => WorkResult {
+                    farmer,
+                    success: true,
+                    zeros,
+                },
+                _ => WorkResult {
+                    farmer,
+                    success: false,
+                    zeros: 0,
+                },
+            };
+            results.push_back(result);
+        }
+        
+        Ok(results)
+    }
+    
+    /// Switch a circle to pooled mode, where members deposit into a vault farmed by the contract (only creator can do this)
+    pub fn enable_pooled_mode(env: Env, caller: Address, circle_id: u32) -> Result<bool, Error> {
+        caller.require_auth();
+        
+        let circle: Circle = match env.storage().instance().get(&DataKey::Circle(circle_id)) {
+            Some(c) => c,
+            None => return Err(Error::CircleDoesNotExist),
+        };
+        
+        if circle.creator != caller {
+            return Err(Error::NotOwner);
+        }
+        
+        if circle.betrayed {
+            return Err(Error::CircleBetrayed);
+        }
+        
+        if env.storage().instance().has(&DataKey::Vault(circle_id)) {
+            return Err(Error::AlreadyPooled);
+        }
+        
+        let vault = Vault {
+            total_assets: 0,
+            total_shares: 0,
+            planted: 0,
+        };
+        env.storage().instance().set(&DataKey::Vault(circle_id), &vault);
+        
+        Ok(true)
+    }
+    
+    /// Deposit KALE into a pooled circle's vault in exchange for shares
+    pub fn deposit(env: Env, member: Address, circle_id: u32, amount: i128) -> Result<i128, Error> {
+        member.require_auth();
+        
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        
+        let mut vault = Self::load_vault(&env, circle_id)?;
+        
+        if !Self::is_circle_member(&env, circle_id, &member) {
+            return Err(Error::NotAMember);
+        }
+        
+        let shares = if vault.total_shares == 0 || vault.total_assets == 0 {
+            amount
+        } else {
+            amount * vault.total_shares / vault.total_assets
+        };
+        
+        if shares <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        
+        let kale_client = Self::get_kale_client(&env)?;
+        Self::safe_transfer_to_contract(&env, &kale_client, &member, amount)?;
+        
+        vault.total_assets += amount;
+        vault.total_shares += shares;
+        env.storage().instance().set(&DataKey::Vault(circle_id), &vault);
+        Self::adjust_vault_liquid(&env, amount);
+        
+        let held: i128 = env.storage().instance().get(&DataKey::VaultShares(circle_id, member.clone())).unwrap_or(0);
+        env.storage().instance().set(&DataKey::VaultShares(circle_id, member), &(held + shares));
+        
+        Ok(shares)
+    }
+    
+    /// Redeem vault shares for KALE; only the unplanted part of the vault can be withdrawn
+    pub fn withdraw(env: Env, member: Address, circle_id: u32, shares: i128) -> Result<i128, Error> {
+        member.require_auth();
+        
+        if shares <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        
+        let mut vault = Self::load_vault(&env, circle_id)?;
+        
+        let held: i128 = env.storage().instance().get(&DataKey::VaultShares(circle_id, member.clone())).unwrap_or(0);
+        if shares > held {
+            return Err(Error::InsufficientShares);
+        }
+        
+        let amount = shares * vault.total_assets / vault.total_shares;
+        if amount > vault.total_assets - vault.planted {
+            return Err(Error::VaultIlliquid);
+        }
+        
+        if amount > 0 {
+            let kale_client = Self::get_kale_client(&env)?;
+            Self::safe_transfer_from_contract(&env, &kale_client, &member, amount)?;
+        }
+        
+        vault.total_assets -= amount;
+        vault.total_shares -= shares;
+        env.storage().instance().set(&DataKey::Vault(circle_id), &vault);
+        Self::adjust_vault_liquid(&env, -amount);
+        
+        if held == shares {
+            env.storage().instance().remove(&DataKey::VaultShares(circle_id, member));
+        } else {
+            env.storage().instance().set(&DataKey::VaultShares(circle_id, member), &(held - shares));
+        }
+        
+        Ok(amount)
+    }
+    
+    /// Stake vault KALE in the mining contract with the contract as farmer
+    pub fn vault_plant(env: Env, caller: Address, circle_id: u32, amount: i128) -> Result<bool, Error> {
+        caller.require_auth();
+        
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        
+        let mut vault = Self::load_vault(&env, circle_id)?;
+        Self::require_vault_operator(&env, circle_id, &caller)?;
+        
+        if env.storage().instance().has(&DataKey::CircleFrozen(circle_id)) {
+            return Err(Error::CircleFrozen);
+        }
+        
+        // The contract is a single farmer, so only one vault can be planted at a time
+        if let Some(active) = env.storage().instance().get::<DataKey, u32>(&DataKey::ActiveFarmCircle) {
+            if active != circle_id {
+                return Err(Error::FarmerBusy);
+            }
+        }
+        
+        if amount > vault.total_assets - vault.planted {
+            return Err(Error::VaultIlliquid);
+        }
+        
+        let kale_client = Self::get_kale_client(&env)?;
+        let mining_client = Self::get_mining_client(&env)?;
+        
+        // Planting burns the stake from the farmer, which here is the contract itself
+        let contract = env.current_contract_address();
+        env.authorize_as_current_contract(vec![
+            &env,
+            InvokerContractAuthEntry::Contract(SubContractInvocation {
+                context: ContractContext {
+                    contract: kale_client.address.clone(),
+                    fn_name: Symbol::new(&env, "burn"),
+                    args: (contract.clone(), amount).into_val(&env),
+                },
+                sub_invocations: vec![&env],
+            }),
+        ]);
+        
+        match mining_client.try_plant(&contract, &amount) {
+            Ok(_) => {},
+            Err(_) => return Err(Error::HarvestFailed),
+        }
+        
+        vault.planted += amount;
+        env.storage().instance().set(&DataKey::Vault(circle_id), &vault);
+        env.storage().instance().set(&DataKey::ActiveFarmCircle, &circle_id);
+        Self::adjust_vault_liquid(&env, -amount);
+        Self::record_treasury_flow(&env, DataKey::TotalDistributed, amount);
+        
+        Ok(true)
+    }
+    
+    /// Submit proof of work for the vault's stake
+    pub fn vault_work(env: Env, caller: Address, circle_id: u32, hash: BytesN<32>, nonce: u64) -> Result<u32, Error> {
+        caller.require_auth();
+        
+        Self::load_vault(&env, circle_id)?;
+        Self::require_vault_operator(&env, circle_id, &caller)?;
+        
+        let active: Option<u32> = env.storage().instance().get(&DataKey::ActiveFarmCircle);
+        if active != Some(circle_id) {
+            return Err(Error::FarmerBusy);
+        }
+        
+        let mining_client = Self::get_mining_client(&env)?;
+        match mining_client.try_work(&env.current_contract_address(), &hash, &nonce) {
+            Ok(Ok(zeros)) => Ok(zeros),
+            _ => Err(Error::HarvestFailed),
+        }
+    }
+    
+    /// Harvest the vault's stake; the yield accrues to the share price, or to the betrayer once betrayed
+    pub fn vault_harvest(env: Env, caller: Address, circle_id: u32, index: u32) -> Result<i128, Error> {
+        caller.require_auth();
+        
+        let mut vault = Self::load_vault(&env, circle_id)?;
+        
+        let active: Option<u32> = env.storage().instance().get(&DataKey::ActiveFarmCircle);
+        if active != Some(circle_id) {
+            return Err(Error::FarmerBusy);
+        }
+        
+        let circle: Circle = match env.storage().instance().get(&DataKey::Circle(circle_id)) {
+            Some(c) => c,
+            None => return Err(Error::CircleDoesNotExist),
+        };
+        
+        let kale_client = Self::get_kale_client(&env)?;
+        let mining_client = Self::get_mining_client(&env)?;
+        let contract = env.current_contract_address();
+        
+        let balance_before = Self::safe_get_balance(&kale_client, &contract)?;
+        Self::try_harvest(&env, &mining_client, contract.clone(), index)?;
+        let balance_after = Self::safe_get_balance(&kale_client, &contract)?;
+        let returned = (balance_after - balance_before).max(0);
+        Self::record_treasury_flow(&env, DataKey::TotalPooled, returned);
+        
+        // Anything above the stake is yield
+        let stake = vault.planted;
+        let mut yield_amount = (returned - stake).max(0);
+        let mut retained = returned;
+        
+        // Betrayal redirects the vault's yield per the circle's terms; deposits stay with members
+        if circle.betrayed && yield_amount > 0 {
+            let owed = Self::betrayer_share(&env, circle_id, yield_amount);
+            if owed > 0 {
+                for payout in Self::pay_betrayers(&env, &kale_client, circle_id, owed).iter() {
+                    retained -= payout.amount;
+                    yield_amount -= payout.amount;
+                }
+            }
+        }
+        
+        vault.total_assets = vault.total_assets - stake + retained;
+        vault.planted = 0;
+        env.storage().instance().set(&DataKey::Vault(circle_id), &vault);
+        env.storage().instance().remove(&DataKey::ActiveFarmCircle);
+        Self::adjust_vault_liquid(&env, retained);
+        
+        if yield_amount > 0 {
+            Self::update_circle_earnings(&env, circle_id, yield_amount);
+        }
+        
+        Ok(returned)
+    }
+    
+    /// Get a pooled circle's vault and share price
+    pub fn get_share_price(env: Env, circle_id: u32) -> Result<VaultInfo, Error> {
+        let vault = Self::load_vault(&env, circle_id)?;
+        
+        let share_price = if vault.total_shares > 0 {
+            vault.total_assets * SHARE_PRICE_SCALE / vault.total_shares
+        } else {
+            SHARE_PRICE_SCALE
+        };
+        
+        Ok(VaultInfo {
+            circle_id,
+            total_assets: vault.total_assets,
+            total_shares: vault.total_shares,
+            planted: vault.planted,
+            share_price,
+        })
+    }
+    
+    /// Get the vault shares a member holds in a pooled circle
+    pub fn get_vault_shares(env: Env, circle_id: u32, member: Address) -> i128 {
+        env.storage().instance().get(&DataKey::VaultShares(circle_id, member)).unwrap_or(0)
+    }
+    
+    /// Check whether a member's block has already been harvested through a circle; records expire after ~30 days untouched
+    pub fn is_index_harvested(env: Env, member: Address, index: u32) -> bool {
+        env.storage().persistent().has(&DataKey::HarvestedIndex(member, index))
+    }
+    
+    // Harvest a single circle for the given indices and fold the outcome into the call's result
+    fn harvest_circle(env: &Env, circle_id: u32, indices: &Vec<u32>, tip_budget: &mut i128, result: &mut HarvestResult) {
+        // Safely get circle data
+        let circle_opt: Option<Circle> = env.storage().instance().get(&DataKey::Circle(circle_id));
+        
+        let circle = match circle_opt {
+            Some(c) => c,
+            None => {
+                result.failed_harvests += 1;
+                return; // Skip this circle if it doesn't exist
+            }
+        };
+        
+        // Skip if no members
+        if circle.member_count == 0 {
+            return;
+        }
+        
+        // Pooled circles farm through their vault instead, frozen and closed circles don't farm at all
+        if env.storage().instance().has(&DataKey::Vault(circle_id))
+            || env.storage().instance().has(&DataKey::CircleFrozen(circle_id))
+            || env.storage().instance().has(&DataKey::CircleClosed(circle_id)) {
+            return;
+        }
+        
+        // Get all members of this circle safely
+        let members: Vec<Address> = env.storage().instance().get(&DataKey::CircleMembers(circle_id)).unwrap_or(vec![env]);
+        
+        // Settled betrayers have left the game, so their harvests are no longer pulled into the pool
+        let members = if circle.betrayed && Self::load_betrayal_terms(env, circle_id).rule != BetrayalRule::PerpetualRedirect {
+            Self::loyal_members(env, circle_id, &members)
+        } else {
+            members
+        };
+        
+        if members.is_empty() {
+            return;
+        }
+        
+        // Each index is tried once per call; members who already harvested it are skipped later
+        let mut pending_indices: Vec<u32> = vec![env];
+        for index in indices.iter() {
+            if !pending_indices.contains(index) {
+                pending_indices.push_back(index);
+            }
+        }
+        
+        if pending_indices.is_empty() {
+            return;
+        }
+        
+        // Process this circle's harvest with error isolation
+        let mut report = CircleHarvestReport {
+            circle_id,
+            pulls: vec![env],
+            payouts: vec![env],
+            failures: vec![env],
+            remainder: RemainderOutcome { amount: 0, recipient: None, carried_forward: false },
+            total_pooled: 0,
+            total_distributed: 0,
+            keeper_tip: 0,
+        };
+        let outcome = Self::process_circle_harvest(env, &circle, &members, circle_id, &pending_indices, &mut report, tip_budget);
+        result.keeper_tip += report.keeper_tip;
+        result.circles.push_back(report);
+        
+        match outcome {
+            Ok(distributed_amount) => {
+                result.total_distributed += distributed_amount;
+                if distributed_amount > 0 {
+                    result.successful_circles += 1;
+                    // Update circle earnings tracking
+                    Self::update_circle_earnings(env, circle_id, distributed_amount);
+                }
+            },
+            Err(_) => {
+                result.failed_harvests += 1;
+                // Continue processing other circles even if this one fails
+            }
+        }
+    }
+    
+    // Update global totals and pay the keeper once all circles are processed
+    fn finish_harvest(env: &Env, caller: &Address, result: &HarvestResult) {
+        // Update global total earnings
+        if result.total_distributed > 0 {
+            let current_total: i128 = env.storage().instance().get(&DataKey::TotalKaleEarned).unwrap_or(0);
+            env.storage().instance().set(&DataKey::TotalKaleEarned, &(current_total + result.total_distributed));
+        }
+        
+        // Pay the keeper everything withheld for them in one transfer
+        if result.keeper_tip > 0 {
+            if let Ok(kale_client) = Self::get_kale_client(env) {
+                if Self::safe_transfer_from_contract(env, &kale_client, caller, result.keeper_tip).is_err() {
+                    Self::credit_pending(env, caller, result.keeper_tip);
+                }
+            }
+        }
+        Self::update_keeper_stats(env, caller, result.keeper_tip, result.successful_circles);
+    }
+    
+    // Isolated circle processing function that handles its own errors and tracks earnings
+    fn process_circle_harvest(env: &Env, circle: &Circle, members: &Vec<Address>, circle_id: u32, indices: &Vec<u32>, report: &mut CircleHarvestReport, tip_budget: &mut i128) -> Result<i128, Error> {
+        let mut total_circle_harvest = 0i128;
+        
+        // Safe initialization of token clients
+        let kale_client = match Self::get_kale_client(&env) {
+            Ok(client) => client,
+            Err(_) => return Err(Error::TokenTransferFailed),
+        };
+        
+        let mining_client = match Self::get_mining_client(&env) {
+            Ok(client) => client,
+            Err(_) => return Err(Error::HarvestFailed),
+        };
+        
+        // For each member, safely attempt harvest of every index
+        for member in members.iter() {
+            let mut member_harvest = 0i128;
+            let mut harvested_any = false;
+            
+            for index in indices.iter() {
+                // A block a member already harvested can't be harvested again
+                let harvested_key = DataKey::HarvestedIndex(member.clone(), index);
+                if env.storage().persistent().has(&harvested_key) {
+                    continue;
+                }
+                
+                match Self::safe_harvest_member(env, &kale_client, &mining_client, member.clone(), index) {
+                    Ok(harvested_amount) => {
+                        member_harvest += harvested_amount;
+                        harvested_any = true;
+                        
+                        // Only successful harvests are remembered, so failed ones can be retried
+                        env.storage().persistent().set(&harvested_key, &true);
+                        Self::extend_persistent(env, &harvested_key);
+                    },
+                    Err(e) => {
+                        // Individual member harvest failed, continue with others
+                        let reason = if e == Error::HarvestFailed {
+                            HarvestFailureReason::MiningError
+                        } else {
+                            HarvestFailureReason::BalanceReadError
+                        };
+                        report.failures.push_back(MemberFailure { member: member.clone(), reason });
+                    }
+                }
+            }
+            
+            if !harvested_any {
+                continue;
+            }
+            
+            if member_harvest > 0 {
+                // Pull the harvest into the pool
+                match Self::safe_pull_harvest(env, &kale_client, &member, member_harvest) {
+                    Ok(_) => {
+                        total_circle_harvest += member_harvest;
+                        Self::record_member_harvested(env, circle_id, &member, member_harvest);
+                    },
+                    Err(_) => {
+                        // Log the failure but continue with other members
+                        let reason = Self::transfer_failure_reason(env, &kale_client, &member, member_harvest);
+                        report.failures.push_back(MemberFailure { member, reason });
+                        continue;
+                    }
+                }
+            }
+            
+            report.pulls.push_back(MemberPull { member, amount: member_harvest });
+        }
+        
+        report.total_pooled = total_circle_harvest;
+        
+        // Withhold the keeper's cut before the pool is split
+        if total_circle_harvest > 0 {
+            let tip = Self::keeper_tip_for(env, total_circle_harvest, *tip_budget);
+            *tip_budget -= tip;
+            report.keeper_tip = tip;
+            total_circle_harvest -= tip;
+        }
+        
+        // Distribute the pooled harvest if any was collected, rotating by the latest index
+        if total_circle_harvest > 0 {
+            let index = indices.last().unwrap_or(0);
+            match Self::safe_distribute_pooled_harvest(env, circle, circle_id, members, report, total_circle_harvest, index) {
+                Ok(distributed) => {
+                    report.total_distributed = distributed;
+                    
+                    // Update circle's total earnings
+                    let mut updated_circle = circle.clone();
+                    updated_circle.total_kale_earned += distributed;
+                    env.storage().instance().set(&DataKey::Circle(circle_id), &updated_circle);
+                    
+                    Ok(distributed)
+                },
+                Err(_) => Err(Error::TokenTransferFailed),
+            }
+        } else {
+            Ok(0)
+        }
+    }
+    
+    // Safe member harvest function
+    fn safe_harvest_member(env: &Env, kale_client: &token::Client, mining_client: &KaleMiningClient, member: Address, index: u32) -> Result<i128, Error> {
+        // Get balance before harvest
+        let balance_before = match Self::safe_get_balance(&kale_client, &member) {
+            Ok(balance) => balance,
+            Err(_) => return Err(Error::TokenTransferFailed),
+        };
+        
+        // Attempt harvest with error handling
+        match Self::try_harvest(&env, &mining_client, member.clone(), index) {
+            Ok(_) => {
+                // Get balance after harvest
+                let balance_after = match Self::safe_get_balance(&kale_client, &member) {
+                    Ok(balance) => balance,
+                    Err(_) => return Err(Error::TokenTransferFailed),
+                };
+                
+                let harvested = balance_after.saturating_sub(balance_before);
+                Ok(harvested.max(0))
+            },
+            Err(_) => Err(Error::HarvestFailed),
+        }
+    }
+    
+    // Pull a member's harvest into the pool against the allowance they granted on joining
+    fn safe_pull_harvest(env: &Env, kale_client: &token::Client, member: &Address, amount: i128) -> Result<(), Error> {
+        let contract = env.current_contract_address();
+        
+        match kale_client.try_transfer_from(&contract, member, &contract, &amount) {
+            Ok(_) => {
+                Self::record_treasury_flow(env, DataKey::TotalPooled, amount);
+                Ok(())
+            },
+            Err(_) => Err(Error::TokenTransferFailed),
+        }
+    }
+    
+    // Tell an expired allowance apart from other transfer failures
+    fn transfer_failure_reason(env: &Env, kale_client: &token::Client, member: &Address, amount: i128) -> HarvestFailureReason {
+        match kale_client.try_allowance(member, &env.current_contract_address()) {
+            Ok(Ok(allowance)) if allowance < amount => HarvestFailureReason::AllowanceExpired,
+            _ => HarvestFailureReason::TransferError,
+        }
+    }
+    
+    // Safe harvest attempt with comprehensive error handling
+    fn try_harvest(_env: &Env, mining_client: &KaleMiningClient, farmer: Address, index: u32) -> Result<bool, Error> {
+        // This is where the original crash would occur
+        // We need to handle any potential panics or errors from the mining contract
+        match mining_client.try_harvest(&farmer, &index) {
+            Ok(_) => Ok(true),
+            Err(_) => Err(Error::HarvestFailed),
+        }
+    }
+    
+    // Safe token balance check
+    fn safe_get_balance(kale_client: &token::Client, address: &Address) -> Result<i128, Error> {
+        kale_client
+            .try_balance(&address)
+            .map_err(|_| Error::TokenTransferFailed)?
+            .map_err(|_| Error::TokenTransferFailed)
+    }
+    
+    // Safe token transfer to contract
+    fn safe_transfer_to_contract(env: &Env, kale_client: &token::Client, from: &Address, amount: i128) -> Result<(), Error> {
+        if amount <= 0 {
+            return Err(Error::InvalidAmount);
+        }
+        
+        match kale_client.try_transfer(&from, &env.current_contract_address(), &amount) {
+            Ok(_) => {
+                Self::record_treasury_flow(env, DataKey::TotalPooled, amount);
+                Ok(())
+            },
+            Err(_) => Err(Error::TokenTransferFailed),
+        }
+    }
+    
+    // Safe pooled harvest distribution with earnings tracking
+    fn safe_distribute_pooled_harvest(env: &Env, circle: &Circle, circle_id: u32, members: &Vec<Address>, report: &mut CircleHarvestReport, total_harvest: i128, index: u32) -> Result<i128, Error> {
+        if total_harvest <= 0 {
+            return Ok(0);
+        }
+        
+        let kale_client = match Self::get_kale_client(&env) {
+            Ok(client) => client,
+            Err(_) => return Err(Error::TokenTransferFailed),
+        };
+        
+        // Fold in any dust carried over from earlier harvests
+        let mut total_harvest = total_harvest + Self::take_carried_dust(env, circle_id);
+        let mut distributed = 0i128;
+        
+        let loyal;
+        let members = if circle.betrayed {
+            // The betrayal terms decide how much of this harvest goes to the betrayers
+            let owed = Self::betrayer_share(env, circle_id, total_harvest);
+            if owed > 0 {
+                for payout in Self::pay_betrayers(env, &kale_client, circle_id, owed).iter() {
+                    distributed += payout.amount;
+                    total_harvest -= payout.amount;
+                    report.payouts.push_back(payout);
+                }
+            }
+            
+            if total_harvest <= 0 {
+                Self::check_pending_invariant(env, &kale_client);
+                return Ok(distributed);
+            }
+            
+            // Whatever is left belongs to the members who stayed loyal
+            loyal = Self::loyal_members(env, circle_id, members);
+            &loyal
+        } else {
+            members
+        };
+        
+        if !members.is_empty() {
+            // Split according to the circle's distribution policy
+            let mut contributions: Map<Address, i128> = Map::new(env);
+            for pull in report.pulls.iter() {
+                contributions.set(pull.member, pull.amount);
+            }
+            let (creator_cut, shares) = Self::compute_shares(env, circle, circle_id, members, &contributions, total_harvest, index);
+            let mut allocated = 0i128;
+            
+            if creator_cut > 0 {
+                report.payouts.push_back(Self::pay_member(env, &kale_client, circle, circle_id, &circle.creator, creator_cut));
+                allocated += creator_cut;
+            }
+            
+            for (member, share) in members.iter().zip(shares.iter()) {
+                if share > 0 {
+                    report.payouts.push_back(Self::pay_member(env, &kale_client, circle, circle_id, &member, share));
+                    allocated += share;
+                }
+            }
+            distributed += allocated;
+            
+            // Handle the remainder according to the circle's strategy
+            let remainder = total_harvest - allocated;
+            if remainder > 0 {
+                let strategy: RemainderStrategy = env.storage().instance().get(&DataKey::RemainderStrategy(circle_id)).unwrap_or(RemainderStrategy::RotateByIndex);
+                
+                if strategy == RemainderStrategy::CarryForward {
+                    Self::carry_dust(env, circle_id, remainder);
+                    report.remainder = RemainderOutcome { amount: remainder, recipient: None, carried_forward: true };
+                } else {
+                    let recipient = Self::pick_remainder_recipient(env, &strategy, members, index);
+                    report.payouts.push_back(Self::pay_member(env, &kale_client, circle, circle_id, &recipient, remainder));
+                    distributed += remainder;
+                    report.remainder = RemainderOutcome { amount: remainder, recipient: Some(recipient), carried_forward: false };
+                }
+            }
+            
+            Self::check_pending_invariant(env, &kale_client);
+            return Ok(distributed);
+        }
+        
+        Ok(distributed)
+    }
+    
+    // Work out the creator's cut and each member's share under the circle's policy
+    fn compute_shares(env: &Env, circle: &Circle, circle_id: u32, members: &Vec<Address>, contributions: &Map<Address, i128>, total: i128, index: u32) -> (i128, Vec<i128>) {
+        let mut weights: Vec<i128> = vec![env];
+        let mut creator_cut = 0i128;
+        
+        match circle.policy {
+            DistributionPolicy::Equal => {
+                for _ in members.iter() {
+                    weights.push_back(1);
+                }
+            },
+            DistributionPolicy::ProRata => {
+                for member in members.iter() {
+                    weights.push_back(contributions.get(member).unwrap_or(0));
+                }
+            },
+            DistributionPolicy::TenureWeighted => {
+                let now = env.ledger().sequence();
+                for member in members.iter() {
+                    let joined_at: u32 = env.storage().instance().get(&DataKey::MemberJoinedAt(circle_id, member)).unwrap_or(0);
+                    weights.push_back(now.saturating_sub(joined_at) as i128 + 1);
+                }
+            },
+            DistributionPolicy::CreatorCommission(bps) => {
+                creator_cut = total * bps as i128 / 10_000;
+                for _ in members.iter() {
+                    weights.push_back(1);
+                }
+            },
+            DistributionPolicy::WinnerTakesAll => {
+                let winner = index % members.len();
+                for position in 0..members.len() {
+                    weights.push_back(if position == winner { 1 } else { 0 });
+                }
+            },
+        }
+        
+        (creator_cut, Self::split_by_weights(env, total - creator_cut, &weights))
+    }
+    
+    // Split an amount proportionally to the weights, rounding down; equal split if all weights are zero
+    fn split_by_weights(env: &Env, amount: i128, weights: &Vec<i128>) -> Vec<i128> {
+        let mut total_weight = 0i128;
+        for weight in weights.iter() {
+            total_weight += weight;
+        }
+        
+        let mut shares: Vec<i128> = vec![env];
+        for weight in weights.iter() {
+            let share = if total_weight > 0 {
+                amount * weight / total_weight
+            } else {
+                amount / weights.len() as i128
+            };
+            shares.push_back(share);
+        }
+        shares
+    }
+    
+    // Reject policies with out-of-range parameters
+    fn validate_policy(policy: &DistributionPolicy) -> Result<(), Error> {
+        if let DistributionPolicy::CreatorCommission(bps) = policy {
+            if *bps > 10_000 {
+                return Err(Error::InvalidBasisPoints);
+            }
+        }
+        Ok(())
+    }
+    
+    /// Preview how a harvest of the given size would be distributed, without moving tokens.
+    /// Circles that harvests skip get an empty preview whose status says why.
+    pub fn preview_distribution(env: Env, circle_id: u32, hypothetical_total: i128) -> Result<DistributionPreview, Error> {
+        let circle: Circle = match env.storage().instance().get(&DataKey::Circle(circle_id)) {
+            Some(c) => c,
+            None => return Err(Error::CircleDoesNotExist),
+        };
+        
+        if hypothetical_total < 0 {
+            return Err(Error::InvalidAmount);
+        }
+        
+        let members: Vec<Address> = env.storage().instance().get(&DataKey::CircleMembers(circle_id)).unwrap_or(vec![&env]);
+        let carried: i128 = env.storage().instance().get(&DataKey::CarriedDust(circle_id)).unwrap_or(0);
+        let keeper_tip = Self::keeper_tip_for(&env, hypothetical_total, Self::load_keeper_config(&env).max_tip_per_call);
+        let mut total = hypothetical_total - keeper_tip + carried;
+        
+        let status = if env.storage().instance().has(&DataKey::Vault(circle_id)) {
+            PreviewStatus::PooledVault
+        } else if env.storage().instance().has(&DataKey::CircleFrozen(circle_id)) {
+            PreviewStatus::CircleFrozen
+        } else if env.storage().instance().has(&DataKey::CircleClosed(circle_id)) {
+            PreviewStatus::CircleClosed
+        } else {
+            PreviewStatus::Ready
+        };
+        
+        let mut preview = DistributionPreview {
+            circle_id,
+            status: status.clone(),
+            total,
+            payouts: vec![&env],
+            keeper_tip,
+            betrayal_recipient: None,
+            remainder: RemainderOutcome { amount: 0, recipient: None, carried_forward: false },
+        };
+        
+        if status != PreviewStatus::Ready {
+            preview.total = 0;
+            preview.keeper_tip = 0;
+            return Ok(preview);
+        }
+        
+        if total <= 0 {
+            return Ok(preview);
+        }
+        
+        let mut members = members;
+        if circle.betrayed {
+            // Perpetual redirection takes everything, settlements only what is still owed
+            let owed = if Self::load_betrayal_terms(&env, circle_id).rule == BetrayalRule::PerpetualRedirect {
+                total
+            } else {
+                let debt: i128 = env.storage().instance().get(&DataKey::BetrayalDebt(circle_id)).unwrap_or(0);
+                debt.min(total).max(0)
+            };
+            
+            if owed > 0 {
+                let betrayers = Self::load_betrayers(&env, circle_id);
+                for (betrayer, share) in betrayers.iter().zip(Self::split_among_betrayers(&env, owed, betrayers.len()).iter()) {
+                    if share > 0 {
+                        preview.payouts.push_back(MemberPayout { member: betrayer, amount: share, escrowed: false, compounded: 0 });
+                    }
+                }
+                preview.betrayal_recipient = betrayers.first();
+                total -= owed;
+            }
+            
+            members = Self::loyal_members(&env, circle_id, &members);
+        }
+        
+        // The winner rotates by block index, so the whole pot is left undecided
+        if total > 0 && !members.is_empty() && circle.policy == DistributionPolicy::WinnerTakesAll {
+            preview.remainder = RemainderOutcome { amount: total, recipient: None, carried_forward: false };
+        } else if total > 0 && !members.is_empty() {
+            // Without a real harvest, pro-rata splits follow each member's past contributions
+            let mut contributions: Map<Address, i128> = Map::new(&env);
+            for member in members.iter() {
+                let contribution = Self::get_or_default_contribution(&env, circle_id, &member);
+                contributions.set(member, contribution.total_harvested);
+            }
+            
+            let (creator_cut, shares) = Self::compute_shares(&env, &circle, circle_id, &members, &contributions, total, 0);
+            let mut allocated = 0i128;
+            
+            if creator_cut > 0 {
+                preview.payouts.push_back(MemberPayout { member: circle.creator.clone(), amount: creator_cut, escrowed: false, compounded: 0 });
+                allocated += creator_cut;
+            }
+            
+            for (member, share) in members.iter().zip(shares.iter()) {
+                if share > 0 {
+                    preview.payouts.push_back(MemberPayout { member, amount: share, escrowed: false, compounded: 0 });
+                    allocated += share;
+                }
+            }
+            
+            let remainder = total - allocated;
+            if remainder > 0 {
+                let strategy: RemainderStrategy = env.storage().instance().get(&DataKey::RemainderStrategy(circle_id)).unwrap_or(RemainderStrategy::RotateByIndex);
+                let recipient = match strategy {
+                    RemainderStrategy::FirstMember => Some(Self::pick_remainder_recipient(&env, &strategy, &members, 0)),
+                    _ => None,
+                };
+                preview.remainder = RemainderOutcome {
+                    amount: remainder,
+                    recipient,
+                    carried_forward: strategy == RemainderStrategy::CarryForward,
+                };
+            }
+        }
+        
+        Ok(preview)
+    }
+    
+    // Keeper tip on a pooled harvest, limited by what is left of the call's cap
+    fn keeper_tip_for(env: &Env, pooled: i128, tip_budget: i128) -> i128 {
+        let config = Self::load_keeper_config(env);
+        let tip = pooled * config.tip_bps as i128 / 10_000;
+        tip.min(tip_budget).max(0)
+    }
+    
+    fn load_keeper_config(env: &Env) -> KeeperConfig {
+        env.storage().instance().get(&DataKey::KeeperConfig).unwrap_or(KeeperConfig {
+            tip_bps: 0,
+            max_tip_per_call: 0,
+        })
+    }
+    
+    fn update_keeper_stats(env: &Env, keeper: &Address, tip: i128, circles_harvested: u32) {
+        let key = DataKey::KeeperStats(keeper.clone());
+        let mut stats: KeeperStats = env.storage().persistent().get(&key).unwrap_or(KeeperStats {
+            address: keeper.clone(),
+            total_tips: 0,
+            harvest_calls: 0,
+            circles_harvested: 0,
+        });
+        
+        stats.total_tips += tip;
+        stats.harvest_calls += 1;
+        stats.circles_harvested += circles_harvested;
+        env.storage().persistent().set(&key, &stats);
+        Self::extend_persistent(env, &key);
+        
+        // Only keepers who actually earned something compete for a place on the board
+        if tip > 0 {
+            Self::update_leaderboard(env, LeaderboardMetric::KeeperTips, LeaderboardSubject::Player(keeper.clone()), stats.total_tips);
+        }
+    }
+    
+    fn extend_persistent(env: &Env, key: &DataKey) {
+        env.storage().persistent().extend_ttl(key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_EXTEND_TO);
+    }
+    
+    // Pay the betrayer their take, escrowing it if the transfer fails
+    fn pay_betrayer(env: &Env, kale_client: &token::Client, circle_id: u32, betrayer: &Address, amount: i128) -> MemberPayout {
+        // While victims can still retaliate the rewards stay with the contract
+        let hold = Self::revenge_hold_active(env, circle_id);
+        if hold {
+            let key = DataKey::HeldBetrayalRewards(circle_id, betrayer.clone());
+            let held: i128 = env.storage().instance().get(&key).unwrap_or(0);
+            env.storage().instance().set(&key, &(held + amount));
+            Self::record_treasury_flow(env, DataKey::TotalRevengeHeld, amount);
+        }
+        
+        let escrowed = hold || Self::safe_transfer_from_contract(env, kale_client, betrayer, amount).is_err();
+        if escrowed && !hold {
+            // Keep the tokens owed to the betrayer in escrow
+            Self::credit_pending(env, betrayer, amount);
+        }
+        
+        // Update betrayer's earnings; the more a betrayal captures the more trust it costs
+        let captured_points = amount * Self::load_trust_params(env).betrayal_points_per_unit / TRUST_KALE_UNIT;
+        Self::charge_betrayal_trust(env, circle_id, betrayer, captured_points);
+        Self::update_player_earnings_betrayal(env, betrayer, amount);
+        Self::record_member_received(env, circle_id, betrayer, amount);
+        
+        MemberPayout {
+            member: betrayer.clone(),
+            amount,
+            escrowed,
+            compounded: 0,
+        }
+    }
+    
+    // Split the betrayal take equally among all betrayers, the first one getting the remainder.
+    // While the betrayal window is open the take is held and only split once it closes,
+    // since later betrayers share it and a full defection voids it
+    fn pay_betrayers(env: &Env, kale_client: &token::Client, circle_id: u32, amount: i128) -> Vec<MemberPayout> {
+        let betrayers = Self::load_betrayers(env, circle_id);
+        let mut payouts: Vec<MemberPayout> = vec![env];
+        let hold = Self::betrayal_window_open(env, circle_id);
+        let mut held = 0i128;
+        
+        for (betrayer, share) in betrayers.iter().zip(Self::split_among_betrayers(env, amount, betrayers.len()).iter()) {
+            if share <= 0 {
+                continue;
+            }
+            if hold {
+                held += share;
+                payouts.push_back(MemberPayout { member: betrayer, amount: share, escrowed: true, compounded: 0 });
+            } else {
+                payouts.push_back(Self::pay_betrayer(env, kale_client, circle_id, &betrayer, share));
+            }
         }
         
-        HarvestResult {
-            total_distributed,
-            successful_circles,
-            failed_harvests,
+        if held > 0 {
+            let key = DataKey::WindowHeldRewards(circle_id);
+            let current: i128 = env.storage().instance().get(&key).unwrap_or(0);
+            env.storage().instance().set(&key, &(current + held));
+            Self::record_treasury_flow(env, DataKey::TotalWindowHeld, held);
         }
+        payouts
     }
     
-    // Isolated circle processing function that handles its own errors and tracks earnings
-    fn process_circle_harvest(env: &Env, circle: &Circle, members: &Vec<Address>, circle_id: u32, index: u32) -> Result<i128, Error> {
-        let mut total_circle_harvest = 0i128;
-        
-        // Safe initialization of token clients
-        let kale_client = match Self::get_kale_client(&env) {
-            Ok(client) => client,
-            Err(_) => return Err(Error::TokenTransferFailed),
+    // Clear a circle's window hold, returning what it held
+    fn take_window_hold(env: &Env, circle_id: u32) -> i128 {
+        let held: i128 = env.storage().instance().get(&DataKey::WindowHeldRewards(circle_id)).unwrap_or(0);
+        if held > 0 {
+            env.storage().instance().remove(&DataKey::WindowHeldRewards(circle_id));
+            Self::record_treasury_flow(env, DataKey::TotalWindowHeld, -held);
+        }
+        held
+    }
+    
+    // Spoils voided by a full defection go back to the creator, the only player who didn't defect;
+    // if the transfer fails they stay owed as a pending credit, so they are never sweepable surplus
+    fn return_voided_spoils(env: &Env, circle_id: u32, amount: i128) {
+        let circle: Circle = match env.storage().instance().get(&DataKey::Circle(circle_id)) {
+            Some(c) => c,
+            None => return,
         };
         
-        let mining_client = match Self::get_mining_client(&env) {
-            Ok(client) => client,
-            Err(_) => return Err(Error::HarvestFailed),
+        let delivered = match Self::get_kale_client(env) {
+            Ok(kale_client) => Self::safe_transfer_from_contract(env, &kale_client, &circle.creator, amount).is_ok(),
+            Err(_) => false,
         };
+        if !delivered {
+            Self::credit_pending(env, &circle.creator, amount);
+        }
         
-        // For each member, safely attempt harvest
-        for member in members.iter() {
-            match Self::safe_harvest_member(&env, &kale_client, &mining_client, member.clone(), index) {
-                Ok(harvested_amount) => {
-                    if harvested_amount > 0 {
-                        // Safely transfer tokens to contract
-                        match Self::safe_transfer_to_contract(&env, &kale_client, &member, harvested_amount) {
-                            Ok(_) => {
-                                total_circle_harvest += harvested_amount;
-                            },
-                            Err(_) => {
-                                // Log the failure but continue with other members
-                                continue;
-                            }
-                        }
-                    }
-                },
-                Err(_) => {
-                    // Individual member harvest failed, continue with others
-                    continue;
-                }
-            }
+        env.events().publish((symbol_short!("voided"), circle_id), (circle.creator, amount));
+    }
+    
+    fn split_among_betrayers(env: &Env, amount: i128, count: u32) -> Vec<i128> {
+        let mut shares: Vec<i128> = vec![env];
+        if count == 0 {
+            return shares;
         }
         
-        // Distribute the pooled harvest if any was collected
-        if total_circle_harvest > 0 {
-            match Self::safe_distribute_pooled_harvest(&env, &circle, &members, total_circle_harvest) {
-                Ok(distributed) => {
-                    // Update circle's total earnings
-                    let mut updated_circle = circle.clone();
-                    updated_circle.total_kale_earned += distributed;
-                    env.storage().instance().set(&DataKey::Circle(circle_id), &updated_circle);
-                    
-                    Ok(distributed)
-                },
-                Err(_) => Err(Error::TokenTransferFailed),
-            }
+        let share = amount / count as i128;
+        for position in 0..count {
+            shares.push_back(if position == 0 { share + amount % count as i128 } else { share });
+        }
+        shares
+    }
+    
+    // How much of a betrayed circle's harvest the betrayer takes under the circle's terms
+    fn betrayer_share(env: &Env, circle_id: u32, amount: i128) -> i128 {
+        let terms = Self::load_betrayal_terms(env, circle_id);
+        if terms.rule == BetrayalRule::PerpetualRedirect {
+            return amount;
+        }
+        
+        // Settlement rules only pay off the outstanding debt
+        let debt: i128 = env.storage().instance().get(&DataKey::BetrayalDebt(circle_id)).unwrap_or(0);
+        if debt <= 0 {
+            return 0;
+        }
+        
+        let taken = debt.min(amount);
+        if taken == debt {
+            env.storage().instance().remove(&DataKey::BetrayalDebt(circle_id));
+            Self::finish_settlement(env, circle_id, &terms);
         } else {
-            Ok(0)
+            env.storage().instance().set(&DataKey::BetrayalDebt(circle_id), &(debt - taken));
         }
+        taken
     }
     
-    // Safe member harvest function
-    fn safe_harvest_member(env: &Env, kale_client: &token::Client, mining_client: &KaleMiningClient, member: Address, index: u32) -> Result<i128, Error> {
-        // Get balance before harvest
-        let balance_before = match Self::safe_get_balance(&kale_client, &member) {
-            Ok(balance) => balance,
-            Err(_) => return Err(Error::TokenTransferFailed),
-        };
+    // Apply the circle's betrayal terms at the moment of betrayal
+    fn settle_betrayal(env: &Env, circle_id: u32) {
+        let terms = Self::load_betrayal_terms(env, circle_id);
         
-        // Attempt harvest with error handling
-        match Self::try_harvest(&env, &mining_client, member.clone(), index) {
-            Ok(_) => {
-                // Get balance after harvest
-                let balance_after = match Self::safe_get_balance(&kale_client, &member) {
-                    Ok(balance) => balance,
-                    Err(_) => return Err(Error::TokenTransferFailed),
-                };
+        match terms.rule {
+            BetrayalRule::PerpetualRedirect => {},
+            BetrayalRule::TakeRecentHarvests(bps) => {
+                let recent: Vec<i128> = env.storage().instance().get(&DataKey::RecentHarvests(circle_id)).unwrap_or(vec![env]);
+                let mut recent_total = 0i128;
+                for amount in recent.iter() {
+                    recent_total += amount;
+                }
                 
-                let harvested = balance_after.saturating_sub(balance_before);
-                Ok(harvested.max(0))
+                let debt = recent_total * bps as i128 / 10_000;
+                if debt > 0 {
+                    env.storage().instance().set(&DataKey::BetrayalDebt(circle_id), &debt);
+                } else {
+                    Self::finish_settlement(env, circle_id, &terms);
+                }
             },
-            Err(_) => Err(Error::HarvestFailed),
         }
     }
     
-    // Safe harvest attempt with comprehensive error handling
-    fn try_harvest(_env: &Env, mining_client: &KaleMiningClient, farmer: Address, index: u32) -> Result<bool, Error> {
-        // This is where the original crash would occur
-        // We need to handle any potential panics or errors from the mining contract
-        match mining_client.try_harvest(&farmer, &index) {
-            Ok(_) => Ok(true),
-            Err(_) => Err(Error::HarvestFailed),
+    // Once the betrayer is paid, either freeze the circle or let the members carry on
+    fn finish_settlement(env: &Env, circle_id: u32, terms: &BetrayalTerms) {
+        if terms.after == AfterBetrayal::Freeze {
+            env.storage().instance().set(&DataKey::CircleFrozen(circle_id), &true);
         }
     }
     
-    // Safe token balance check
-    fn safe_get_balance(kale_client: &token::Client, address: &Address) -> Result<i128, Error> {
-        kale_client
-            .try_balance(&address)
-            .map_err(|_| Error::TokenTransferFailed)?
-            .map_err(|_| Error::TokenTransferFailed)
+    fn load_betrayal_terms(env: &Env, circle_id: u32) -> BetrayalTerms {
+        env.storage().instance().get(&DataKey::BetrayalTerms(circle_id)).unwrap_or(BetrayalTerms {
+            rule: BetrayalRule::PerpetualRedirect,
+            after: AfterBetrayal::MembersKeepEarnings,
+        })
     }
     
-    // Safe token transfer to contract
-    fn safe_transfer_to_contract(env: &Env, kale_client: &token::Client, from: &Address, amount: i128) -> Result<(), Error> {
-        if amount <= 0 {
-            return Err(Error::InvalidAmount);
+    // Pay a member their share, escrowing it if the transfer fails
+    fn pay_member(env: &Env, kale_client: &token::Client, circle: &Circle, circle_id: u32, member: &Address, amount: i128) -> MemberPayout {
+        // Hold back the member's compounding share before delivering the rest
+        let compounded = Self::withhold_compound_share(env, circle_id, member, amount);
+        let delivered = amount - compounded;
+        
+        let escrowed = delivered > 0 && Self::safe_transfer_from_contract(env, kale_client, member, delivered).is_err();
+        if escrowed {
+            // Record what this member is owed and continue with the others
+            Self::credit_pending(env, member, delivered);
         }
         
-        match kale_client.try_transfer(&from, &env.current_contract_address(), &amount) {
-            Ok(_) => Ok(()),
-            Err(_) => Err(Error::TokenTransferFailed),
+        Self::record_member_received(env, circle_id, member, amount);
+        
+        // Update member's earnings
+        if *member == circle.creator {
+            Self::update_player_earnings_own_circle(env, member, amount);
+        } else {
+            Self::update_player_earnings_joined_circle(env, member, amount);
+        }
+        
+        // Staying in a circle that holds together builds trust, more so the longer the tenure
+        if !circle.betrayed {
+            Self::reward_tenure(env, circle_id, member);
+        }
+        
+        MemberPayout {
+            member: member.clone(),
+            amount,
+            escrowed,
+            compounded,
         }
     }
     
-    // Safe pooled harvest distribution with earnings tracking
-    fn safe_distribute_pooled_harvest(env: &Env, circle: &Circle, members: &Vec<Address>, total_harvest: i128) -> Result<i128, Error> {
-        if total_harvest <= 0 {
-            return Ok(0);
+    // Keep a member's compounding share of a payout in the contract until they plant it
+    fn withhold_compound_share(env: &Env, circle_id: u32, member: &Address, amount: i128) -> i128 {
+        let bps: u32 = env.storage().instance().get(&DataKey::CompoundBps(circle_id, member.clone())).unwrap_or(0);
+        let share = amount * bps as i128 / 10_000;
+        if share <= 0 {
+            return 0;
         }
         
-        let kale_client = match Self::get_kale_client(&env) {
-            Ok(client) => client,
-            Err(_) => return Err(Error::TokenTransferFailed),
+        let key = DataKey::CompoundReserve(member.clone());
+        let reserve: i128 = env.storage().instance().get(&key).unwrap_or(0);
+        env.storage().instance().set(&key, &(reserve + share));
+        Self::record_treasury_flow(env, DataKey::TotalCompoundReserve, share);
+        
+        share
+    }
+    
+    // Pick who receives the remainder of an equal split
+    fn pick_remainder_recipient(env: &Env, strategy: &RemainderStrategy, members: &Vec<Address>, index: u32) -> Address {
+        let position = match strategy {
+            RemainderStrategy::RotateByIndex => index % members.len(),
+            RemainderStrategy::Random => env.prng().gen_range::<u64>(0..members.len() as u64) as u32,
+            _ => 0,
         };
+        members.get(position).unwrap()
+    }
+    
+    // Keep a remainder in the contract for the circle's next harvest
+    fn carry_dust(env: &Env, circle_id: u32, amount: i128) {
+        let current: i128 = env.storage().instance().get(&DataKey::CarriedDust(circle_id)).unwrap_or(0);
+        env.storage().instance().set(&DataKey::CarriedDust(circle_id), &(current + amount));
         
-        if circle.betrayed {
-            // If betrayed, all pooled rewards go to the betrayer
-            if let Some(betrayer) = &circle.betrayer {
-                match Self::safe_transfer_from_contract(&env, &kale_client, betrayer, total_harvest) {
-                    Ok(_) => {
-                        // Update betrayer's earnings
-                        Self::update_player_earnings_betrayal(&env, betrayer, total_harvest);
-                        return Ok(total_harvest);
-                    },
-                    Err(_) => return Err(Error::TokenTransferFailed),
-                }
-            }
-        } else {
-            // Distribute equally among all circle members
-            let member_count = members.len() as i128;
-            
-            if member_count > 0 {
-                let reward_per_member = total_harvest / member_count;
-                let remainder = total_harvest % member_count;
-                let mut distributed = 0i128;
-                
-                // Distribute base amount to all members
-                for member in members.iter() {
-                    if reward_per_member > 0 {
-                        match Self::safe_transfer_from_contract(&env, &kale_client, &member, reward_per_member) {
-                            Ok(_) => {
-                                distributed += reward_per_member;
-                                // Update member's earnings
-                                if member == circle.creator {
-                                    Self::update_player_earnings_own_circle(&env, &member, reward_per_member);
-                                } else {
-                                    Self::update_player_earnings_joined_circle(&env, &member, reward_per_member);
-                                }
-                            },
-                            Err(_) => {
-                                // Continue with other members even if one transfer fails
-                                continue;
-                            }
-                        }
-                    }
-                }
-                
-                // Give remainder to the first member
-                if remainder > 0 && !members.is_empty() {
-                    let first_member = members.get(0).unwrap();
-                    match Self::safe_transfer_from_contract(&env, &kale_client, &first_member, remainder) {
-                        Ok(_) => {
-                            distributed += remainder;
-                            // Update first member's earnings
-                            if first_member == circle.creator {
-                                Self::update_player_earnings_own_circle(&env, &first_member, remainder);
-                            } else {
-                                Self::update_player_earnings_joined_circle(&env, &first_member, remainder);
-                            }
-                        },
-                        Err(_) => {
-                            // Remainder transfer failed, but we still distributed the base amounts
-                        }
-                    }
-                }
-                
-                return Ok(distributed);
-            }
+        let total: i128 = env.storage().instance().get(&DataKey::TotalCarriedDust).unwrap_or(0);
+        env.storage().instance().set(&DataKey::TotalCarriedDust, &(total + amount));
+    }
+    
+    // Release a circle's carried dust so it can be distributed
+    fn take_carried_dust(env: &Env, circle_id: u32) -> i128 {
+        let carried: i128 = env.storage().instance().get(&DataKey::CarriedDust(circle_id)).unwrap_or(0);
+        if carried > 0 {
+            env.storage().instance().remove(&DataKey::CarriedDust(circle_id));
+            let total: i128 = env.storage().instance().get(&DataKey::TotalCarriedDust).unwrap_or(0);
+            env.storage().instance().set(&DataKey::TotalCarriedDust, &(total - carried));
         }
-        
-        Ok(0)
+        carried
     }
     
     // Safe token transfer from contract
@@ -590,11 +3116,97 @@ impl Contract {
         }
         
         match kale_client.try_transfer(&env.current_contract_address(), &to, &amount) {
-            Ok(_) => Ok(()),
+            Ok(_) => {
+                Self::record_treasury_flow(env, DataKey::TotalDistributed, amount);
+                Ok(())
+            },
             Err(_) => Err(Error::TokenTransferFailed),
         }
     }
     
+    // Keep the treasury counters in step with every KALE transfer
+    fn record_treasury_flow(env: &Env, counter: DataKey, amount: i128) {
+        let current: i128 = env.storage().instance().get(&counter).unwrap_or(0);
+        env.storage().instance().set(&counter, &(current + amount));
+    }
+    
+    /// Reconcile the contract's KALE balance against its own accounting
+    pub fn audit(env: Env) -> TreasuryAudit {
+        let kale_balance = match Self::get_kale_client(&env) {
+            Ok(client) => Self::safe_get_balance(&client, &env.current_contract_address()).unwrap_or(0),
+            Err(_) => 0,
+        };
+        
+        let total_pooled: i128 = env.storage().instance().get(&DataKey::TotalPooled).unwrap_or(0);
+        let total_distributed: i128 = env.storage().instance().get(&DataKey::TotalDistributed).unwrap_or(0);
+        let total_pending_credits: i128 = env.storage().instance().get(&DataKey::TotalPendingCredits).unwrap_or(0);
+        let total_carried_dust: i128 = env.storage().instance().get(&DataKey::TotalCarriedDust).unwrap_or(0);
+        
+        // Everything pooled and not yet paid out should still be held by the contract
+        let unexplained_surplus = kale_balance - (total_pooled - total_distributed);
+        
+        TreasuryAudit {
+            kale_balance,
+            total_pooled,
+            total_distributed,
+            total_pending_credits,
+            total_carried_dust,
+            unexplained_surplus,
+        }
+    }
+    
+    // Escrow an undelivered payout so the member can claim it later
+    fn credit_pending(env: &Env, member: &Address, amount: i128) {
+        let key = DataKey::PendingCredit(member.clone());
+        let current: i128 = env.storage().instance().get(&key).unwrap_or(0);
+        env.storage().instance().set(&key, &(current + amount));
+        
+        let total: i128 = env.storage().instance().get(&DataKey::TotalPendingCredits).unwrap_or(0);
+        env.storage().instance().set(&DataKey::TotalPendingCredits, &(total + amount));
+    }
+    
+    // Pending credits must always be backed by KALE held by the contract
+    fn check_pending_invariant(env: &Env, kale_client: &token::Client) {
+        let total_pending: i128 = env.storage().instance().get(&DataKey::TotalPendingCredits).unwrap_or(0);
+        if total_pending <= 0 {
+            return;
+        }
+        
+        // If the balance can't be read there is nothing to compare against
+        if let Ok(balance) = Self::safe_get_balance(kale_client, &env.current_contract_address()) {
+            if total_pending > balance {
+                panic_with_error!(env, Error::PendingCreditsExceedBalance);
+            }
+        }
+    }
+    
+    /// Claim KALE escrowed from payouts that failed during distribution
+    pub fn claim_pending(env: Env, member: Address) -> Result<i128, Error> {
+        member.require_auth();
+        
+        let key = DataKey::PendingCredit(member.clone());
+        let amount: i128 = env.storage().instance().get(&key).unwrap_or(0);
+        if amount <= 0 {
+            return Err(Error::NothingToClaim);
+        }
+        
+        let kale_client = Self::get_kale_client(&env)?;
+        Self::safe_transfer_from_contract(&env, &kale_client, &member, amount)?;
+        
+        env.storage().instance().remove(&key);
+        let total: i128 = env.storage().instance().get(&DataKey::TotalPendingCredits).unwrap_or(0);
+        env.storage().instance().set(&DataKey::TotalPendingCredits, &(total - amount));
+        
+        Self::check_pending_invariant(&env, &kale_client);
+        
+        Ok(amount)
+    }
+    
+    /// Get the KALE a member can claim from failed payouts
+    pub fn get_pending_credit(env: Env, member: Address) -> i128 {
+        env.storage().instance().get(&DataKey::PendingCredit(member)).unwrap_or(0)
+    }
+    
     // Safe KALE client initialization
     fn get_kale_client(env: &Env) -> Result<token::Client, Error> {
         let kale_asset_address = Address::from_str(&env, "CAAVU2UQJLMZ3GUZFM56KVNHLPA3ZSSNR4VP2U53YBXFD2GI3QLIVHZZ");
@@ -625,7 +3237,10 @@ impl Contract {
         
         for player in all_players.iter() {
             if let Some(stats) = env.storage().instance().get::<DataKey, PlayerStats>(&DataKey::PlayerStats(player.clone())) {
-                let trust_score = stats.circles_joined as i32 - stats.circles_betrayed as i32;
+                let restored: i128 = env.storage().instance().get(&DataKey::TotalTrustRestored(player.clone())).unwrap_or(0);
+                let restored_trust = (restored / TRUST_SCALE).clamp(0, u32::MAX as i128) as u32;
+                let record = Self::current_trust(&env, &player, &Self::load_trust_params(&env));
+                let trust_score = (record.score / TRUST_SCALE).clamp(i32::MIN as i128, i32::MAX as i128) as i32;
                 
                 let betrayal_ratio = if stats.circles_joined > 0 {
                     (stats.circles_betrayed * 100) / stats.circles_joined
@@ -646,6 +3261,8 @@ impl Contract {
                     circles_betrayed: stats.circles_betrayed,
                     times_betrayed: stats.times_betrayed,
                     trust_score,
+                    trust_version: record.version,
+                    restored_trust,
                     betrayal_ratio,
                     total_kale_earned: stats.total_kale_earned,
                     kale_per_circle,
@@ -673,28 +3290,111 @@ impl Contract {
         env.storage().instance().get(&DataKey::CircleEarnings(circle_id))
     }
     
+    /// Get how much a member has harvested into and received from a circle
+    pub fn get_member_contribution(env: Env, circle_id: u32, member: Address) -> Option<MemberContribution> {
+        env.storage().instance().get(&DataKey::MemberContribution(circle_id, member))
+    }
+    
+    /// Get the contribution breakdown of every current member of a circle
+    pub fn get_circle_contributions(env: Env, circle_id: u32) -> Vec<MemberContribution> {
+        let members: Vec<Address> = env.storage().instance().get(&DataKey::CircleMembers(circle_id)).unwrap_or(vec![&env]);
+        let mut contributions: Vec<MemberContribution> = vec![&env];
+        
+        for member in members.iter() {
+            contributions.push_back(Self::get_or_default_contribution(&env, circle_id, &member));
+        }
+        
+        contributions
+    }
+    
     /// Get total KALE earned across all circles
     pub fn get_total_kale_earned(env: Env) -> i128 {
         env.storage().instance().get(&DataKey::TotalKaleEarned).unwrap_or(0)
     }
     
-    /// Get top earning circles
+    /// Get top earning circles, highest first; circles not on the board yet follow in creation order
     pub fn get_top_earning_circles(env: Env, limit: u32) -> Vec<CircleEarnings> {
-        let all_circle_ids: Vec<u32> = env.storage().instance().get(&DataKey::AllCircleIds).unwrap_or(vec![&env]);
         let mut circle_earnings: Vec<CircleEarnings> = vec![&env];
+        let mut ranked: Vec<u32> = vec![&env];
+        
+        for entry in Self::get_leaderboard(env.clone(), LeaderboardMetric::CircleEarnings, 0, limit).iter() {
+            if let LeaderboardSubject::Circle(circle_id) = entry.subject {
+                if let Some(earnings) = env.storage().instance().get::<DataKey, CircleEarnings>(&DataKey::CircleEarnings(circle_id)) {
+                    circle_earnings.push_back(earnings);
+                    ranked.push_back(circle_id);
+                }
+            }
+        }
         
+        // Circles that haven't harvested since the board was introduced aren't on it
+        let all_circle_ids: Vec<u32> = env.storage().instance().get(&DataKey::AllCircleIds).unwrap_or(vec![&env]);
         for circle_id in all_circle_ids.iter() {
-            if let Some(earnings) = env.storage().instance().get::<DataKey, CircleEarnings>(&DataKey::CircleEarnings(circle_id.clone())) {
+            if circle_earnings.len() >= limit {
+                break;
+            }
+            if ranked.contains(circle_id) {
+                continue;
+            }
+            if let Some(earnings) = env.storage().instance().get::<DataKey, CircleEarnings>(&DataKey::CircleEarnings(circle_id)) {
                 circle_earnings.push_back(earnings);
             }
         }
         
-        // Sort by total_earned (descending) and return top results
-        // Note: Soroban SDK doesn't have built-in sorting, so we'd need to implement it manually
-        // For now, just return all earnings (client can sort)
         circle_earnings
     }
     
+    /// Rank existing players or circles on a leaderboard, a page at a time (admin only); returns where the next page starts.
+    /// Boards only pick subjects up as they change, so this seeds them with everyone who predates them.
+    pub fn seed_leaderboard(env: Env, metric: LeaderboardMetric, start: u32, limit: u32) -> Result<u32, Error> {
+        Self::require_admin(&env)?;
+        
+        if metric == LeaderboardMetric::CircleEarnings {
+            let all_circle_ids: Vec<u32> = env.storage().instance().get(&DataKey::AllCircleIds).unwrap_or(vec![&env]);
+            let end = start.saturating_add(limit).min(all_circle_ids.len());
+            for circle_id in all_circle_ids.slice(start.min(end)..end).iter() {
+                if let Some(earnings) = env.storage().instance().get::<DataKey, CircleEarnings>(&DataKey::CircleEarnings(circle_id)) {
+                    Self::update_leaderboard(&env, metric, LeaderboardSubject::Circle(circle_id), earnings.total_earned);
+                }
+            }
+            return Ok(end);
+        }
+        
+        // Keepers aren't listed anywhere, so their board only fills as they earn tips
+        if metric == LeaderboardMetric::KeeperTips {
+            return Ok(start);
+        }
+        
+        let all_players: Vec<Address> = env.storage().instance().get(&DataKey::AllPlayers).unwrap_or(vec![&env]);
+        let end = start.saturating_add(limit).min(all_players.len());
+        let params = Self::load_trust_params(&env);
+        for player in all_players.slice(start.min(end)..end).iter() {
+            let value = match metric {
+                LeaderboardMetric::TrustScore => {
+                    let record = Self::current_trust(&env, &player, &params);
+                    env.storage().instance().set(&DataKey::TrustRecord(player.clone()), &record);
+                    record.score
+                },
+                _ => match env.storage().instance().get::<DataKey, PlayerStats>(&DataKey::PlayerStats(player.clone())) {
+                    Some(stats) if metric == LeaderboardMetric::KaleEarned => stats.total_kale_earned,
+                    Some(stats) => stats.circles_betrayed as i128,
+                    None => continue,
+                },
+            };
+            Self::update_leaderboard(&env, metric, LeaderboardSubject::Player(player), value);
+        }
+        Ok(end)
+    }
+    
+    /// Get a page of a sorted leaderboard; the entry at position i has rank offset + i + 1.
+    /// TrustScore values are as of each player's last trust update; get_trust_record gives the decayed score.
+    pub fn get_leaderboard(env: Env, metric: LeaderboardMetric, offset: u32, limit: u32) -> Vec<LeaderboardEntry> {
+        let board: Vec<LeaderboardEntry> = env.storage().persistent().get(&DataKey::Leaderboard(metric)).unwrap_or(vec![&env]);
+        
+        let start = offset.min(board.len());
+        let end = start.saturating_add(limit).min(board.len());
+        board.slice(start..end)
+    }
+    
     /// Get total participation statistics including earnings
     pub fn get_total_stats(env: Env) -> (u32, u32, u32, u32, i128) {
         let all_players: Vec<Address> = env.storage().instance().get(&DataKey::AllPlayers).unwrap_or(vec![&env]);
@@ -741,6 +3441,31 @@ impl Contract {
         let mut stats: PlayerStats = env.storage().instance().get(&DataKey::PlayerStats(player.clone())).unwrap();
         stats.circles_betrayed += 1;
         env.storage().instance().set(&DataKey::PlayerStats(player.clone()), &stats);
+        Self::update_leaderboard(env, LeaderboardMetric::Betrayals, LeaderboardSubject::Player(player.clone()), stats.circles_betrayed as i128);
+    }
+    
+    fn update_player_stats_retaliation(env: &Env, player: &Address) {
+        Self::ensure_player_exists(env, player);
+        
+        let mut stats: PlayerStats = env.storage().instance().get(&DataKey::PlayerStats(player.clone())).unwrap();
+        stats.retaliations += 1;
+        env.storage().instance().set(&DataKey::PlayerStats(player.clone()), &stats);
+    }
+    
+    fn update_player_stats_retaliated_against(env: &Env, player: &Address) {
+        Self::ensure_player_exists(env, player);
+        
+        let mut stats: PlayerStats = env.storage().instance().get(&DataKey::PlayerStats(player.clone())).unwrap();
+        stats.times_retaliated_against += 1;
+        env.storage().instance().set(&DataKey::PlayerStats(player.clone()), &stats);
+    }
+    
+    fn update_player_stats_restitution(env: &Env, player: &Address, amount: i128) {
+        Self::ensure_player_exists(env, player);
+        
+        let mut stats: PlayerStats = env.storage().instance().get(&DataKey::PlayerStats(player.clone())).unwrap();
+        stats.restitution_paid += amount;
+        env.storage().instance().set(&DataKey::PlayerStats(player.clone()), &stats);
     }
     
     fn update_player_stats_was_betrayed(env: &Env, player: &Address) {
@@ -764,6 +3489,7 @@ impl Contract {
         let mut stats: PlayerStats = env.storage().instance().get(&DataKey::PlayerStats(player.clone())).unwrap();
         stats.total_kale_earned += amount;
         env.storage().instance().set(&DataKey::PlayerStats(player.clone()), &stats);
+        Self::update_leaderboard(env, LeaderboardMetric::KaleEarned, LeaderboardSubject::Player(player.clone()), stats.total_kale_earned);
     }
     
     fn update_player_earnings_joined_circle(env: &Env, player: &Address, amount: i128) {
@@ -773,25 +3499,133 @@ impl Contract {
         earnings.total_kale_earned += amount;
         earnings.kale_earned_from_join_circles += amount;
         env.storage().instance().set(&DataKey::PlayerEarnings(player.clone()), &earnings);
-        
-        // Also update player stats
-        let mut stats: PlayerStats = env.storage().instance().get(&DataKey::PlayerStats(player.clone())).unwrap();
-        stats.total_kale_earned += amount;
-        env.storage().instance().set(&DataKey::PlayerStats(player.clone()), &stats);
+        
+        // Also update player stats
+        let mut stats: PlayerStats = env.storage().instance().get(&DataKey::PlayerStats(player.clone())).unwrap();
+        stats.total_kale_earned += amount;
+        env.storage().instance().set(&DataKey::PlayerStats(player.clone()), &stats);
+        Self::update_leaderboard(env, LeaderboardMetric::KaleEarned, LeaderboardSubject::Player(player.clone()), stats.total_kale_earned);
+    }
+    
+    fn update_player_earnings_betrayal(env: &Env, player: &Address, amount: i128) {
+        Self::ensure_player_earnings_exists(env, player);
+        
+        let mut earnings: PlayerEarnings = env.storage().instance().get(&DataKey::PlayerEarnings(player.clone())).unwrap();
+        earnings.total_kale_earned += amount;
+        earnings.kale_earned_from_betrayals += amount;
+        env.storage().instance().set(&DataKey::PlayerEarnings(player.clone()), &earnings);
+        
+        // Also update player stats
+        let mut stats: PlayerStats = env.storage().instance().get(&DataKey::PlayerStats(player.clone())).unwrap();
+        stats.total_kale_earned += amount;
+        env.storage().instance().set(&DataKey::PlayerStats(player.clone()), &stats);
+        Self::update_leaderboard(env, LeaderboardMetric::KaleEarned, LeaderboardSubject::Player(player.clone()), stats.total_kale_earned);
+    }
+    
+    fn update_player_earnings_retaliation(env: &Env, player: &Address, amount: i128) {
+        Self::ensure_player_earnings_exists(env, player);
+        
+        let mut earnings: PlayerEarnings = env.storage().instance().get(&DataKey::PlayerEarnings(player.clone())).unwrap();
+        earnings.total_kale_earned += amount;
+        earnings.kale_from_retaliation += amount;
+        env.storage().instance().set(&DataKey::PlayerEarnings(player.clone()), &earnings);
+        
+        let mut stats: PlayerStats = env.storage().instance().get(&DataKey::PlayerStats(player.clone())).unwrap();
+        stats.total_kale_earned += amount;
+        env.storage().instance().set(&DataKey::PlayerStats(player.clone()), &stats);
+        Self::update_leaderboard(env, LeaderboardMetric::KaleEarned, LeaderboardSubject::Player(player.clone()), stats.total_kale_earned);
+    }
+    
+    fn update_player_earnings_restitution(env: &Env, player: &Address, amount: i128) {
+        Self::ensure_player_earnings_exists(env, player);
+        
+        let mut earnings: PlayerEarnings = env.storage().instance().get(&DataKey::PlayerEarnings(player.clone())).unwrap();
+        earnings.total_kale_earned += amount;
+        earnings.kale_from_restitution += amount;
+        env.storage().instance().set(&DataKey::PlayerEarnings(player.clone()), &earnings);
+        
+        let mut stats: PlayerStats = env.storage().instance().get(&DataKey::PlayerStats(player.clone())).unwrap();
+        stats.total_kale_earned += amount;
+        env.storage().instance().set(&DataKey::PlayerStats(player.clone()), &stats);
+        Self::update_leaderboard(env, LeaderboardMetric::KaleEarned, LeaderboardSubject::Player(player.clone()), stats.total_kale_earned);
+    }
+    
+    fn update_player_earnings_lost_to_retaliation(env: &Env, player: &Address, amount: i128) {
+        Self::ensure_player_earnings_exists(env, player);
+        
+        let mut earnings: PlayerEarnings = env.storage().instance().get(&DataKey::PlayerEarnings(player.clone())).unwrap();
+        earnings.kale_lost_to_retaliation += amount;
+        env.storage().instance().set(&DataKey::PlayerEarnings(player.clone()), &earnings);
     }
     
-    fn update_player_earnings_betrayal(env: &Env, player: &Address, amount: i128) {
+    fn update_player_earnings_compounded(env: &Env, player: &Address, amount: i128) {
         Self::ensure_player_earnings_exists(env, player);
         
         let mut earnings: PlayerEarnings = env.storage().instance().get(&DataKey::PlayerEarnings(player.clone())).unwrap();
-        earnings.total_kale_earned += amount;
-        earnings.kale_earned_from_betrayals += amount;
+        earnings.kale_compounded += amount;
         env.storage().instance().set(&DataKey::PlayerEarnings(player.clone()), &earnings);
+    }
+    
+    fn get_or_default_contribution(env: &Env, circle_id: u32, member: &Address) -> MemberContribution {
+        env.storage().instance().get(&DataKey::MemberContribution(circle_id, member.clone())).unwrap_or(
+            MemberContribution {
+                circle_id,
+                member: member.clone(),
+                total_harvested: 0,
+                total_received: 0,
+                harvests_participated: 0,
+            }
+        )
+    }
+    
+    fn record_member_harvested(env: &Env, circle_id: u32, member: &Address, amount: i128) {
+        let mut contribution = Self::get_or_default_contribution(env, circle_id, member);
+        contribution.total_harvested += amount;
+        contribution.harvests_participated += 1;
+        env.storage().instance().set(&DataKey::MemberContribution(circle_id, member.clone()), &contribution);
+    }
+    
+    fn record_member_received(env: &Env, circle_id: u32, member: &Address, amount: i128) {
+        let mut contribution = Self::get_or_default_contribution(env, circle_id, member);
+        contribution.total_received += amount;
+        env.storage().instance().set(&DataKey::MemberContribution(circle_id, member.clone()), &contribution);
+    }
+    
+    // Move a subject to its sorted place in a bounded leaderboard, dropping whoever falls off the end
+    fn update_leaderboard(env: &Env, metric: LeaderboardMetric, subject: LeaderboardSubject, value: i128) {
+        let key = DataKey::Leaderboard(metric);
+        let mut board: Vec<LeaderboardEntry> = env.storage().persistent().get(&key).unwrap_or(vec![env]);
         
-        // Also update player stats
-        let mut stats: PlayerStats = env.storage().instance().get(&DataKey::PlayerStats(player.clone())).unwrap();
-        stats.total_kale_earned += amount;
-        env.storage().instance().set(&DataKey::PlayerStats(player.clone()), &stats);
+        match board.iter().position(|entry| entry.subject == subject) {
+            Some(existing) => {
+                board.remove(existing as u32);
+            },
+            None => {
+                // A subject that can't make a full board leaves it untouched, so there is nothing to write
+                if board.len() >= LEADERBOARD_SIZE && board.last().is_some_and(|last| value <= last.value) {
+                    return;
+                }
+            },
+        }
+        
+        // Ties keep the subject that got there first ahead
+        let mut position = board.len();
+        for (i, entry) in board.iter().enumerate() {
+            if value > entry.value {
+                position = i as u32;
+                break;
+            }
+        }
+        
+        if position < LEADERBOARD_SIZE {
+            board.insert(position, LeaderboardEntry { subject, value });
+            if board.len() > LEADERBOARD_SIZE {
+                board.pop_back();
+            }
+        }
+        
+        env.storage().persistent().set(&key, &board);
+        Self::extend_persistent(env, &key);
     }
     
     fn update_circle_earnings(env: &Env, circle_id: u32, amount: i128) {
@@ -815,6 +3649,15 @@ impl Contract {
         earnings.last_harvest_amount = amount;
         
         env.storage().instance().set(&DataKey::CircleEarnings(circle_id), &earnings);
+        Self::update_leaderboard(env, LeaderboardMetric::CircleEarnings, LeaderboardSubject::Circle(circle_id), earnings.total_earned);
+        
+        // Keep a short window of recent harvests for betrayal settlements
+        let mut recent: Vec<i128> = env.storage().instance().get(&DataKey::RecentHarvests(circle_id)).unwrap_or(vec![env]);
+        recent.push_back(amount);
+        while recent.len() > RECENT_HARVEST_WINDOW {
+            recent.pop_front();
+        }
+        env.storage().instance().set(&DataKey::RecentHarvests(circle_id), &recent);
         
         // Also update the circle's total earnings
         if let Some(mut circle) = env.storage().instance().get::<DataKey, Circle>(&DataKey::Circle(circle_id)) {
@@ -833,6 +3676,9 @@ impl Contract {
                 circles_betrayed: 0,
                 times_betrayed: 0,
                 total_kale_earned: 0,
+                retaliations: 0,
+                times_retaliated_against: 0,
+                restitution_paid: 0,
             };
             env.storage().instance().set(&DataKey::PlayerStats(player.clone()), &stats);
             
@@ -855,6 +3701,10 @@ impl Contract {
                 kale_earned_from_own_circles: 0,
                 kale_earned_from_join_circles: 0,
                 kale_earned_from_betrayals: 0,
+                kale_compounded: 0,
+                kale_from_retaliation: 0,
+                kale_lost_to_retaliation: 0,
+                kale_from_restitution: 0,
             };
             env.storage().instance().set(&DataKey::PlayerEarnings(player.clone()), &earnings);
         }
@@ -882,6 +3732,10 @@ impl Contract {
                     creator: circle.creator,
                     member_count: circle.member_count,
                     total_kale_earned: circle.total_kale_earned,
+                    policy: circle.policy,
+                    join_bond: env.storage().instance().get(&DataKey::JoinBond(circle_id_val)).unwrap_or(0),
+                    total_bonded: env.storage().instance().get(&DataKey::CircleBonded(circle_id_val)).unwrap_or(0),
+                    closed: env.storage().instance().has(&DataKey::CircleClosed(circle_id_val)),
                 };
                 circle_infos.push_back(info);
             }
@@ -936,6 +3790,579 @@ impl Contract {
         vec![&env, String::from_str(&env, "Hello"), to]
     }
     
+    // Require the stored admin's authorization
+    fn require_admin(env: &Env) -> Result<Address, Error> {
+        let admin: Address = match env.storage().instance().get(&DataKey::Admin) {
+            Some(a) => a,
+            None => return Err(Error::NotInitialized),
+        };
+        admin.require_auth();
+        Ok(admin)
+    }
+    
+    // KALE held by the contract on behalf of members
+    fn get_member_liabilities(env: &Env) -> i128 {
+        let pending: i128 = env.storage().instance().get(&DataKey::TotalPendingCredits).unwrap_or(0);
+        let carried: i128 = env.storage().instance().get(&DataKey::TotalCarriedDust).unwrap_or(0);
+        let vaults: i128 = env.storage().instance().get(&DataKey::TotalVaultLiquid).unwrap_or(0);
+        let revenge: i128 = env.storage().instance().get(&DataKey::TotalRevengeHeld).unwrap_or(0);
+        let bonds: i128 = env.storage().instance().get(&DataKey::TotalBonds).unwrap_or(0);
+        let window_held: i128 = env.storage().instance().get(&DataKey::TotalWindowHeld).unwrap_or(0);
+        let compound_reserve: i128 = env.storage().instance().get(&DataKey::TotalCompoundReserve).unwrap_or(0);
+        pending + carried + vaults + revenge + bonds + window_held + compound_reserve
+    }
+    
+    fn load_vault(env: &Env, circle_id: u32) -> Result<Vault, Error> {
+        match env.storage().instance().get(&DataKey::Vault(circle_id)) {
+            Some(v) => Ok(v),
+            None => Err(Error::NotPooledCircle),
+        }
+    }
+    
+    // Creator and members can drive the vault's farming
+    fn require_vault_operator(env: &Env, circle_id: u32, caller: &Address) -> Result<(), Error> {
+        let circle: Circle = match env.storage().instance().get(&DataKey::Circle(circle_id)) {
+            Some(c) => c,
+            None => return Err(Error::CircleDoesNotExist),
+        };
+        
+        if circle.creator != *caller && !Self::is_circle_member(env, circle_id, caller) {
+            return Err(Error::NotAMember);
+        }
+        Ok(())
+    }
+    
+    fn adjust_vault_liquid(env: &Env, delta: i128) {
+        let current: i128 = env.storage().instance().get(&DataKey::TotalVaultLiquid).unwrap_or(0);
+        env.storage().instance().set(&DataKey::TotalVaultLiquid, &(current + delta));
+    }
+    
+    // Mark the circle as betrayed, update stats and settle according to the circle's terms
+    fn apply_betrayal(env: &Env, circle_id: u32, circle: &mut Circle, betrayer: &Address) {
+        let mut betrayers = Self::load_betrayers(env, circle_id);
+        betrayers.push_back(betrayer.clone());
+        env.storage().instance().set(&DataKey::CircleBetrayers(circle_id), &betrayers);
+        
+        // Update scoreboard stats
+        Self::charge_betrayal_trust(env, circle_id, betrayer, Self::load_trust_params(env).betrayal_points);
+        Self::update_player_stats_betrayed(env, betrayer);
+        
+        if circle.betrayed {
+            Self::forfeit_join_bond(env, circle, circle_id, betrayer);
+            
+            // Joining an open betrayal: if every member has now defected, nobody gets anything
+            let window = Self::load_betrayal_window(env, circle_id);
+            let members: Vec<Address> = env.storage().instance().get(&DataKey::CircleMembers(circle_id)).unwrap_or(vec![env]);
+            if window.rule == MultiBetrayalRule::PrisonersDilemma && Self::loyal_members(env, circle_id, &members).is_empty() {
+                env.storage().instance().remove(&DataKey::BetrayalDebt(circle_id));
+                env.storage().instance().set(&DataKey::CircleFrozen(circle_id), &true);
+                
+                // What the betrayers were paid during the window is voided too
+                let voided = Self::take_window_hold(env, circle_id);
+                if voided > 0 {
+                    Self::return_voided_spoils(env, circle_id, voided);
+                }
+            }
+            return;
+        }
+        
+        // Mark circle as betrayed and record the first betrayer
+        circle.betrayed = true;
+        circle.betrayer = Some(betrayer.clone());
+        env.storage().instance().set(&DataKey::Circle(circle_id), circle);
+        env.storage().instance().set(&DataKey::FirstBetrayalLedger(circle_id), &env.ledger().sequence());
+
+        Self::update_player_stats_was_betrayed(env, &circle.creator);
+        
+        // Settle the betrayal according to the circle's terms
+        Self::settle_betrayal(env, circle_id);
+        
+        Self::forfeit_join_bond(env, circle, circle_id, betrayer);
+    }
+    
+    // A circle can be betrayed once, or again by other members while its betrayal window is open
+    fn check_can_betray(env: &Env, circle_id: u32, circle: &Circle, betrayer: &Address) -> Result<(), Error> {
+        if env.storage().instance().has(&DataKey::CircleClosed(circle_id)) {
+            return Err(Error::CircleClosed);
+        }
+        
+        // Mutiny circles only fall to a quorum vote
+        if env.storage().instance().has(&DataKey::MutinyConfig(circle_id)) {
+            return Err(Error::MutinyOnly);
+        }
+        
+        if !circle.betrayed {
+            return Ok(());
+        }
+        
+        if !Self::betrayal_window_open(env, circle_id) || Self::load_betrayers(env, circle_id).contains(betrayer) || env.storage().instance().has(&DataKey::CircleFrozen(circle_id)) {
+            return Err(Error::CircleBetrayed);
+        }
+        Ok(())
+    }
+    
+    // Betray the circle on behalf of every mutineer; the pot is shared among them
+    fn apply_mutiny(env: &Env, circle_id: u32, circle: &mut Circle, mutineers: &Vec<Address>) {
+        circle.betrayed = true;
+        circle.betrayer = mutineers.first();
+        env.storage().instance().set(&DataKey::Circle(circle_id), circle);
+        env.storage().instance().set(&DataKey::CircleBetrayers(circle_id), mutineers);
+        env.storage().instance().set(&DataKey::FirstBetrayalLedger(circle_id), &env.ledger().sequence());
+        
+        let betrayal_points = Self::load_trust_params(env).betrayal_points;
+        for mutineer in mutineers.iter() {
+            Self::charge_betrayal_trust(env, circle_id, &mutineer, betrayal_points);
+            Self::update_player_stats_betrayed(env, &mutineer);
+        }
+        Self::update_player_stats_was_betrayed(env, &circle.creator);
+        
+        let pot = Self::take_carried_dust(env, circle_id);
+        if pot > 0 {
+            if let Ok(kale_client) = Self::get_kale_client(env) {
+                Self::pay_betrayers(env, &kale_client, circle_id, pot);
+                Self::check_pending_invariant(env, &kale_client);
+            }
+        }
+        
+        Self::settle_betrayal(env, circle_id);
+        
+        for mutineer in mutineers.iter() {
+            Self::forfeit_join_bond(env, circle, circle_id, &mutineer);
+        }
+    }
+    
+    // Votes of current members in the running round, and how many are needed
+    fn mutiny_status(env: &Env, circle_id: u32, config: &MutinyConfig) -> MutinyStatus {
+        let members: Vec<Address> = env.storage().instance().get(&DataKey::CircleMembers(circle_id)).unwrap_or(vec![env]);
+        let votes_needed = ((members.len() as u64 * config.quorum_bps as u64).div_ceil(10_000) as u32).max(1);
+        
+        let mut status = MutinyStatus {
+            circle_id,
+            voters: vec![env],
+            votes_needed,
+            opened_at: 0,
+            expires_at: 0,
+            active: false,
+        };
+        
+        if let Some(votes) = env.storage().instance().get::<DataKey, MutinyVotes>(&DataKey::MutinyVotes(circle_id)) {
+            status.opened_at = votes.opened_at;
+            status.expires_at = votes.opened_at.saturating_add(config.voting_window);
+            status.active = env.ledger().sequence() <= status.expires_at;
+            
+            // Votes of members who left or were kicked no longer count
+            for voter in votes.voters.iter() {
+                if members.contains(&voter) {
+                    status.voters.push_back(voter);
+                }
+            }
+        }
+        status
+    }
+    
+    // Take the circle's join bond from a new member into escrow
+    fn post_join_bond(env: &Env, circle_id: u32, member: &Address) -> Result<(), Error> {
+        let bond: i128 = env.storage().instance().get(&DataKey::JoinBond(circle_id)).unwrap_or(0);
+        if bond <= 0 {
+            return Ok(());
+        }
+        
+        let kale_client = Self::get_kale_client(env)?;
+        Self::safe_transfer_to_contract(env, &kale_client, member, bond)?;
+        
+        env.storage().instance().set(&DataKey::MemberBond(circle_id, member.clone()), &bond);
+        Self::record_treasury_flow(env, DataKey::CircleBonded(circle_id), bond);
+        Self::record_treasury_flow(env, DataKey::TotalBonds, bond);
+        Ok(())
+    }
+    
+    // Release a member's bond from escrow, returning how much was held
+    fn take_join_bond(env: &Env, circle_id: u32, member: &Address) -> i128 {
+        let bond: i128 = env.storage().instance().get(&DataKey::MemberBond(circle_id, member.clone())).unwrap_or(0);
+        if bond > 0 {
+            env.storage().instance().remove(&DataKey::MemberBond(circle_id, member.clone()));
+            Self::record_treasury_flow(env, DataKey::CircleBonded(circle_id), -bond);
+            Self::record_treasury_flow(env, DataKey::TotalBonds, -bond);
+        }
+        bond
+    }
+    
+    // Give a departing member their bond back, escrowing it if the transfer fails
+    fn refund_join_bond(env: &Env, circle_id: u32, member: &Address) {
+        let bond = Self::take_join_bond(env, circle_id, member);
+        if bond <= 0 {
+            return;
+        }
+        
+        let refunded = match Self::get_kale_client(env) {
+            Ok(kale_client) => Self::safe_transfer_from_contract(env, &kale_client, member, bond).is_ok(),
+            Err(_) => false,
+        };
+        if !refunded {
+            Self::credit_pending(env, member, bond);
+        }
+    }
+    
+    // A betrayer's bond is split among the members who stayed loyal
+    fn forfeit_join_bond(env: &Env, circle: &Circle, circle_id: u32, betrayer: &Address) {
+        let bond = Self::take_join_bond(env, circle_id, betrayer);
+        if bond <= 0 {
+            return;
+        }
+        
+        let members: Vec<Address> = env.storage().instance().get(&DataKey::CircleMembers(circle_id)).unwrap_or(vec![env]);
+        let loyal = Self::loyal_members(env, circle_id, &members);
+        
+        // With nobody left to compensate the bond stays with the circle
+        let kale_client = match Self::get_kale_client(env) {
+            Ok(client) if !loyal.is_empty() => client,
+            _ => {
+                Self::carry_dust(env, circle_id, bond);
+                return;
+            }
+        };
+        
+        let share = bond / loyal.len() as i128;
+        if share > 0 {
+            for member in loyal.iter() {
+                Self::pay_member(env, &kale_client, circle, circle_id, &member, share);
+            }
+        }
+        
+        let remainder = bond - share * loyal.len() as i128;
+        if remainder > 0 {
+            Self::carry_dust(env, circle_id, remainder);
+        }
+        Self::check_pending_invariant(env, &kale_client);
+    }
+    
+    // Stop a circle harvesting and give its members their bonds back
+    fn mark_closed(env: &Env, circle_id: u32) {
+        env.storage().instance().set(&DataKey::CircleClosed(circle_id), &true);
+        
+        let members: Vec<Address> = env.storage().instance().get(&DataKey::CircleMembers(circle_id)).unwrap_or(vec![env]);
+        for member in members.iter() {
+            Self::refund_join_bond(env, circle_id, &member);
+        }
+    }
+    
+    // Store a new circle under the next ID with its members, making it the creator's circle
+    fn store_new_circle(env: &Env, circle: &Circle, members: &Vec<Address>) -> u32 {
+        // Get next available circle ID
+        let circle_id = Self::get_next_circle_id(env);
+        
+        // Store the circle
+        env.storage().instance().set(&DataKey::Circle(circle_id), circle);
+        
+        // Mark the creator as having created a circle
+        env.storage().instance().set(&DataKey::CreatedCircle(circle.creator.clone()), &circle_id);
+        
+        env.storage().instance().set(&DataKey::CircleMembers(circle_id), members);
+        
+        // Add this circle ID to the list of all circles
+        let mut all_circles: Vec<u32> = env.storage().instance().get(&DataKey::AllCircleIds).unwrap_or(vec![env]);
+        all_circles.push_back(circle_id);
+        env.storage().instance().set(&DataKey::AllCircleIds, &all_circles);
+        
+        // Increment the circle ID counter
+        env.storage().instance().set(&DataKey::NextCircleId, &(circle_id + 1));
+        
+        // Initialize circle earnings tracking
+        let circle_earnings = CircleEarnings {
+            circle_id,
+            total_earned: 0,
+            total_harvests: 0,
+            average_per_harvest: 0,
+            last_harvest_amount: 0,
+        };
+        env.storage().instance().set(&DataKey::CircleEarnings(circle_id), &circle_earnings);
+        
+        circle_id
+    }
+    
+    // The creator and the members who stayed loyal
+    fn betrayal_victims(env: &Env, circle_id: u32, circle: &Circle) -> Vec<Address> {
+        let members: Vec<Address> = env.storage().instance().get(&DataKey::CircleMembers(circle_id)).unwrap_or(vec![env]);
+        let mut victims = Self::loyal_members(env, circle_id, &members);
+        victims.push_front(circle.creator.clone());
+        victims
+    }
+    
+    // Restitution buys back one point per RESTITUTION_PER_TRUST_POINT, up to half the points lost to betrayals
+    fn legacy_trust_score(stats: &PlayerStats) -> (i32, u32) {
+        let restorable = stats.circles_betrayed.div_ceil(2);
+        let bought = (stats.restitution_paid / RESTITUTION_PER_TRUST_POINT).clamp(0, restorable as i128) as u32;
+        (stats.circles_joined as i32 - stats.circles_betrayed as i32 + bought as i32, bought)
+    }
+    
+    fn load_trust_params(env: &Env) -> TrustParams {
+        env.storage().instance().get(&DataKey::TrustParams).unwrap_or(TrustParams {
+            version: 1,
+            half_life_ledgers: 518_400, // ~30 days
+            join_points: TRUST_SCALE,
+            betrayal_points: TRUST_SCALE,
+            betrayal_points_per_unit: TRUST_SCALE,
+            tenure_points: TRUST_SCALE / 10,
+            restitution_points_per_unit: TRUST_SCALE,
+        })
+    }
+    
+    // A player's trust decayed to now; players without a record for this version start from their stats
+    fn current_trust(env: &Env, player: &Address, params: &TrustParams) -> TrustRecord {
+        let now = env.ledger().sequence();
+        
+        match env.storage().instance().get::<DataKey, TrustRecord>(&DataKey::TrustRecord(player.clone())) {
+            Some(record) if record.version == params.version => TrustRecord {
+                score: Self::decay_trust(record.score, now.saturating_sub(record.last_updated), params.half_life_ledgers),
+                last_updated: now,
+                version: record.version,
+            },
+            _ => {
+                let seed = match env.storage().instance().get::<DataKey, PlayerStats>(&DataKey::PlayerStats(player.clone())) {
+                    Some(stats) => Self::legacy_trust_score(&stats).0 as i128 * TRUST_SCALE,
+                    None => 0,
+                };
+                TrustRecord { score: seed, last_updated: now, version: params.version }
+            },
+        }
+    }
+    
+    // Halve the score every half-life, interpolating linearly within the last one
+    fn decay_trust(score: i128, elapsed: u32, half_life: u32) -> i128 {
+        let halvings = elapsed / half_life;
+        if halvings >= 64 {
+            return 0;
+        }
+        
+        let halved = score / (1i128 << halvings);
+        let into_half_life = (elapsed % half_life) as i128;
+        halved - halved * into_half_life / (2 * half_life as i128)
+    }
+    
+    fn adjust_trust(env: &Env, player: &Address, delta: i128) {
+        let score = Self::record_trust(env, player, delta);
+        Self::update_leaderboard(env, LeaderboardMetric::TrustScore, LeaderboardSubject::Player(player.clone()), score);
+    }
+    
+    // Apply a trust change to the player's record only, returning the new score
+    fn record_trust(env: &Env, player: &Address, delta: i128) -> i128 {
+        let mut record = Self::current_trust(env, player, &Self::load_trust_params(env));
+        record.score += delta;
+        env.storage().instance().set(&DataKey::TrustRecord(player.clone()), &record);
+        record.score
+    }
+    
+    // Take trust from a betrayer, remembering what this betrayal has cost them so far
+    fn charge_betrayal_trust(env: &Env, circle_id: u32, betrayer: &Address, points: i128) {
+        if points <= 0 {
+            return;
+        }
+        
+        Self::adjust_trust(env, betrayer, -points);
+        
+        let key = DataKey::BetrayalTrustCost(circle_id, betrayer.clone());
+        let cost: i128 = env.storage().instance().get(&key).unwrap_or(0);
+        env.storage().instance().set(&key, &(cost + points));
+    }
+    
+    // Credit trust for restitution, up to half the points the betrayal of this circle cost
+    fn restore_trust(env: &Env, circle_id: u32, betrayer: &Address, amount: i128) {
+        let cost: i128 = env.storage().instance().get(&DataKey::BetrayalTrustCost(circle_id, betrayer.clone())).unwrap_or(0);
+        let key = DataKey::TrustRestored(circle_id, betrayer.clone());
+        let restored: i128 = env.storage().instance().get(&key).unwrap_or(0);
+        
+        let earned = amount * Self::load_trust_params(env).restitution_points_per_unit / TRUST_KALE_UNIT;
+        let credit = earned.min(cost / 2 - restored);
+        if credit <= 0 {
+            return;
+        }
+        
+        Self::adjust_trust(env, betrayer, credit);
+        env.storage().instance().set(&key, &(restored + credit));
+        
+        let total_key = DataKey::TotalTrustRestored(betrayer.clone());
+        let total: i128 = env.storage().instance().get(&total_key).unwrap_or(0);
+        env.storage().instance().set(&total_key, &(total + credit));
+    }
+    
+    // Full tenure points once a member has been in the circle for a whole half-life
+    fn reward_tenure(env: &Env, circle_id: u32, member: &Address) {
+        let params = Self::load_trust_params(env);
+        if params.tenure_points == 0 {
+            return;
+        }
+        
+        let joined_at: u32 = env.storage().instance().get(&DataKey::MemberJoinedAt(circle_id, member.clone())).unwrap_or(0);
+        let tenure = env.ledger().sequence().saturating_sub(joined_at).min(params.half_life_ledgers);
+        let points = params.tenure_points * tenure as i128 / params.half_life_ledgers as i128;
+        if points > 0 {
+            // Tenure accrues on every payout, so it reaches the trust board with the player's next ranked change or a reseed
+            Self::record_trust(env, member, points);
+        }
+    }
+    
+    // Betrayers of circles with revenge terms must post a deposit the victims can slash
+    fn post_betrayal_deposit(env: &Env, circle_id: u32, betrayer: &Address) -> Result<(), Error> {
+        let terms: RevengeTerms = match env.storage().instance().get(&DataKey::RevengeTerms(circle_id)) {
+            Some(t) => t,
+            None => return Ok(()),
+        };
+        
+        if terms.deposit <= 0 {
+            return Ok(());
+        }
+        
+        let kale_client = Self::get_kale_client(env)?;
+        Self::safe_transfer_to_contract(env, &kale_client, betrayer, terms.deposit)?;
+        
+        env.storage().instance().set(&DataKey::BetrayalDeposit(circle_id, betrayer.clone()), &terms.deposit);
+        Self::record_treasury_flow(env, DataKey::TotalRevengeHeld, terms.deposit);
+        Ok(())
+    }
+    
+    // Return a deposit posted for a betrayal that never happened
+    fn refund_betrayal_deposit(env: &Env, circle_id: u32, member: &Address) -> Result<(), Error> {
+        let key = DataKey::BetrayalDeposit(circle_id, member.clone());
+        let deposit: i128 = env.storage().instance().get(&key).unwrap_or(0);
+        if deposit <= 0 {
+            return Ok(());
+        }
+        
+        env.storage().instance().remove(&key);
+        Self::record_treasury_flow(env, DataKey::TotalRevengeHeld, -deposit);
+        
+        let kale_client = Self::get_kale_client(env)?;
+        if Self::safe_transfer_from_contract(env, &kale_client, member, deposit).is_err() {
+            Self::credit_pending(env, member, deposit);
+        }
+        Ok(())
+    }
+    
+    fn revenge_window_open(env: &Env, circle_id: u32, circle: &Circle) -> bool {
+        if !circle.betrayed {
+            return false;
+        }
+        
+        match env.storage().instance().get::<DataKey, RevengeTerms>(&DataKey::RevengeTerms(circle_id)) {
+            Some(terms) => {
+                let first_ledger: u32 = env.storage().instance().get(&DataKey::FirstBetrayalLedger(circle_id)).unwrap_or(0);
+                env.ledger().sequence() <= first_ledger.saturating_add(terms.window_ledgers)
+            },
+            None => false,
+        }
+    }
+    
+    // Betrayer rewards are held only while a retaliation can still happen
+    fn revenge_hold_active(env: &Env, circle_id: u32) -> bool {
+        if env.storage().instance().has(&DataKey::Retaliated(circle_id)) {
+            return false;
+        }
+        
+        match env.storage().instance().get::<DataKey, Circle>(&DataKey::Circle(circle_id)) {
+            Some(circle) => Self::revenge_window_open(env, circle_id, &circle),
+            None => false,
+        }
+    }
+    
+    // Other members can still join the betrayal, so betrayer payouts are held
+    fn betrayal_window_open(env: &Env, circle_id: u32) -> bool {
+        let window = Self::load_betrayal_window(env, circle_id);
+        if window.window_ledgers == 0 {
+            return false;
+        }
+        
+        match env.storage().instance().get::<DataKey, u32>(&DataKey::FirstBetrayalLedger(circle_id)) {
+            Some(first_ledger) => env.ledger().sequence() <= first_ledger.saturating_add(window.window_ledgers),
+            None => false,
+        }
+    }
+    
+    fn load_betrayal_window(env: &Env, circle_id: u32) -> BetrayalWindow {
+        env.storage().instance().get(&DataKey::BetrayalWindow(circle_id)).unwrap_or(BetrayalWindow {
+            window_ledgers: 0,
+            rule: MultiBetrayalRule::ShareSpoils,
+        })
+    }
+    
+    // Everyone who betrayed the circle, falling back to the single recorded betrayer
+    fn load_betrayers(env: &Env, circle_id: u32) -> Vec<Address> {
+        if let Some(betrayers) = env.storage().instance().get::<DataKey, Vec<Address>>(&DataKey::CircleBetrayers(circle_id)) {
+            return betrayers;
+        }
+        
+        let mut betrayers: Vec<Address> = vec![env];
+        if let Some(circle) = env.storage().instance().get::<DataKey, Circle>(&DataKey::Circle(circle_id)) {
+            if let Some(betrayer) = circle.betrayer {
+                betrayers.push_back(betrayer);
+            }
+        }
+        betrayers
+    }
+    
+    // Members of a circle who haven't betrayed it
+    fn loyal_members(env: &Env, circle_id: u32, members: &Vec<Address>) -> Vec<Address> {
+        let betrayers = Self::load_betrayers(env, circle_id);
+        let mut loyal: Vec<Address> = vec![env];
+        for member in members.iter() {
+            if !betrayers.contains(&member) {
+                loyal.push_back(member);
+            }
+        }
+        loyal
+    }
+    
+    // The value a sealed betrayal commits to
+    fn betrayal_commitment(env: &Env, salt: &BytesN<32>, member: &Address) -> BytesN<32> {
+        let mut preimage = Bytes::from_array(env, &salt.to_array());
+        preimage.append(&member.clone().to_xdr(env));
+        env.crypto().sha256(&preimage).into()
+    }
+    
+    // Drop a member from a circle and everything tied to their membership
+    fn remove_member(env: &Env, circle_id: u32, circle: &mut Circle, member: &Address) {
+        let mut members: Vec<Address> = env.storage().instance().get(&DataKey::CircleMembers(circle_id)).unwrap_or(vec![env]);
+        if let Some(position) = members.first_index_of(member) {
+            members.remove(position);
+        }
+        env.storage().instance().set(&DataKey::CircleMembers(circle_id), &members);
+        
+        circle.member_count = circle.member_count.saturating_sub(1);
+        env.storage().instance().set(&DataKey::Circle(circle_id), circle);
+        
+        let mut wallet_circles: Vec<u32> = env.storage().instance().get(&DataKey::WalletCircle(member.clone())).unwrap_or(vec![env]);
+        if let Some(position) = wallet_circles.first_index_of(circle_id) {
+            wallet_circles.remove(position);
+        }
+        env.storage().instance().set(&DataKey::WalletCircle(member.clone()), &wallet_circles);
+        
+        env.storage().instance().remove(&DataKey::MemberJoinedAt(circle_id, member.clone()));
+        env.storage().instance().remove(&DataKey::PlantConsent(circle_id, member.clone()));
+        env.storage().instance().remove(&DataKey::CompoundBps(circle_id, member.clone()));
+        env.storage().instance().remove(&DataKey::BetrayalCommitment(circle_id, member.clone()));
+    }
+    
+    // A betrayer must be a member whose tenure has reached the minimum
+    fn check_betrayal_tenure(env: &Env, circle_id: u32, member: &Address) -> Result<(), Error> {
+        if !Self::is_circle_member(env, circle_id, member) {
+            return Err(Error::NotAMember);
+        }
+        
+        // Members from before join ledgers were recorded count as long-standing
+        let joined_at: u32 = env.storage().instance().get(&DataKey::MemberJoinedAt(circle_id, member.clone())).unwrap_or(0);
+        let min_tenure: u32 = env.storage().instance().get(&DataKey::MinBetrayalTenure).unwrap_or(DEFAULT_MIN_BETRAYAL_TENURE);
+        
+        if env.ledger().sequence() < joined_at.saturating_add(min_tenure) {
+            return Err(Error::BetrayalTooEarly);
+        }
+        Ok(())
+    }
+    
+    // Check membership against the circle's member list
+    fn is_circle_member(env: &Env, circle_id: u32, wallet: &Address) -> bool {
+        let members: Vec<Address> = env.storage().instance().get(&DataKey::CircleMembers(circle_id)).unwrap_or(vec![env]);
+        members.contains(wallet)
+    }
+    
     // Helper function to get next circle ID
     fn get_next_circle_id(env: &Env) -> u32 {
         env.storage().instance().get(&DataKey::NextCircleId).unwrap_or(1)
@@ -957,4 +4384,6 @@ impl Contract {
         }
         Ok(true)
     }
-}
\ No newline at end of file
+}
+
+mod test;
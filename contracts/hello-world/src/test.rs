@@ -1,12 +1,179 @@
 #![cfg(test)]
 
 use super::*;
-use soroban_sdk::{vec, Env, String};
+use soroban_sdk::testutils::{Address as _, Ledger as _};
+
+const KALE_ADDRESS: &str = "CAAVU2UQJLMZ3GUZFM56KVNHLPA3ZSSNR4VP2U53YBXFD2GI3QLIVHZZ";
+const MINING_ADDRESS: &str = "CDSWUUXGPWDZG76ISK6SUCVPZJMD5YUV66J2FXFXFGDX25XKZJIEITAO";
+const PASSWORD: &str = "kale";
+
+#[contracttype]
+enum MockKey {
+    Balance(Address),
+    Blocked(Address),
+    Allowance(Address, Address),
+    Reward(Address, u32),
+    Zeros(Address),
+}
+
+// Minimal KALE token: balances, transfers and a switch to make transfers to an address fail
+#[contract]
+pub struct MockKale;
+
+#[contractimpl]
+impl MockKale {
+    pub fn mint(env: Env, to: Address, amount: i128) {
+        let balance = Self::balance(env.clone(), to.clone());
+        env.storage().persistent().set(&MockKey::Balance(to), &(balance + amount));
+    }
+
+    pub fn set_blocked(env: Env, id: Address, blocked: bool) {
+        env.storage().persistent().set(&MockKey::Blocked(id), &blocked);
+    }
+
+    pub fn balance(env: Env, id: Address) -> i128 {
+        env.storage().persistent().get(&MockKey::Balance(id)).unwrap_or(0)
+    }
+
+    pub fn burn(env: Env, from: Address, amount: i128) {
+        from.require_auth();
+
+        let balance = Self::balance(env.clone(), from.clone());
+        if balance < amount {
+            panic!("burn exceeds balance");
+        }
+        env.storage().persistent().set(&MockKey::Balance(from), &(balance - amount));
+    }
+
+    pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+        from.require_auth();
+        Self::move_balance(&env, from, to, amount);
+    }
+
+    pub fn transfer_from(env: Env, spender: Address, from: Address, to: Address, amount: i128) {
+        spender.require_auth();
+
+        let allowance = Self::allowance(env.clone(), from.clone(), spender.clone());
+        if allowance < amount {
+            panic!("allowance exceeded");
+        }
+        env.storage().persistent().set(&MockKey::Allowance(from.clone(), spender), &(allowance - amount));
+        Self::move_balance(&env, from, to, amount);
+    }
+
+    pub fn approve(env: Env, from: Address, spender: Address, amount: i128, _expiration_ledger: u32) {
+        from.require_auth();
+        env.storage().persistent().set(&MockKey::Allowance(from, spender), &amount);
+    }
+
+    pub fn allowance(env: Env, from: Address, spender: Address) -> i128 {
+        env.storage().persistent().get(&MockKey::Allowance(from, spender)).unwrap_or(0)
+    }
+}
+
+impl MockKale {
+    fn move_balance(env: &Env, from: Address, to: Address, amount: i128) {
+        let blocked: bool = env.storage().persistent().get(&MockKey::Blocked(to.clone())).unwrap_or(false);
+        let from_balance = Self::balance(env.clone(), from.clone());
+        if blocked || from_balance < amount {
+            panic!("transfer failed");
+        }
+
+        env.storage().persistent().set(&MockKey::Balance(from), &(from_balance - amount));
+        Self::mint(env.clone(), to, amount);
+    }
+}
+
+// Mining contract that burns stakes, accepts work and pays out rewards set up by the test, once per farmer and block
+#[contract]
+pub struct MockMining;
+
+#[contractimpl]
+impl MockMining {
+    pub fn set_reward(env: Env, farmer: Address, index: u32, amount: i128) {
+        env.storage().persistent().set(&MockKey::Reward(farmer, index), &amount);
+    }
+
+    pub fn plant(env: Env, farmer: Address, amount: i128) {
+        farmer.require_auth();
+        MockKaleClient::new(&env, &Address::from_str(&env, KALE_ADDRESS)).burn(&farmer, &amount);
+    }
+
+    pub fn set_zeros(env: Env, farmer: Address, zeros: u32) {
+        env.storage().persistent().set(&MockKey::Zeros(farmer), &zeros);
+    }
+
+    pub fn work(env: Env, farmer: Address, _hash: BytesN<32>, _nonce: u64) -> u32 {
+        match env.storage().persistent().get(&MockKey::Zeros(farmer)) {
+            Some(zeros) => zeros,
+            None => panic!("farmer has not planted"),
+        }
+    }
+
+    pub fn harvest(env: Env, farmer: Address, index: u32) -> i128 {
+        let key = MockKey::Reward(farmer.clone(), index);
+        let reward: i128 = match env.storage().persistent().get(&key) {
+            Some(reward) => reward,
+            None => panic!("nothing to harvest"),
+        };
+        env.storage().persistent().remove(&key);
+
+        MockKaleClient::new(&env, &Address::from_str(&env, KALE_ADDRESS)).mint(&farmer, &reward);
+        reward
+    }
+}
+
+fn setup(env: &Env) -> (ContractClient<'_>, MockKaleClient<'_>, MockMiningClient<'_>, Address) {
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let kale_id = env.register_at(&Address::from_str(env, KALE_ADDRESS), MockKale, ());
+    let mining_id = env.register_at(&Address::from_str(env, MINING_ADDRESS), MockMining, ());
+
+    let admin = Address::generate(env);
+    let contract_id = env.register(Contract, (admin.clone(),));
+
+    (
+        ContractClient::new(env, &contract_id),
+        MockKaleClient::new(env, &kale_id),
+        MockMiningClient::new(env, &mining_id),
+        admin,
+    )
+}
+
+fn password_hash(env: &Env) -> BytesN<32> {
+    env.crypto().sha256(&Bytes::from_slice(env, PASSWORD.as_bytes())).into()
+}
+
+// A circle with a creator and the given number of members
+fn create_circle(env: &Env, client: &ContractClient, members: u32) -> (u32, Address, Vec<Address>) {
+    create_circle_with_policy(env, client, DistributionPolicy::Equal, members)
+}
+
+fn create_circle_with_policy(env: &Env, client: &ContractClient, policy: DistributionPolicy, members: u32) -> (u32, Address, Vec<Address>) {
+    let creator = Address::generate(env);
+    let circle_id = client.create_circle_with_policy(&creator, &String::from_str(env, "circle"), &password_hash(env), &policy);
+
+    let mut joined: Vec<Address> = vec![env];
+    for _ in 0..members {
+        let member = Address::generate(env);
+        client.join_circle(&member, &circle_id, &String::from_str(env, PASSWORD));
+        joined.push_back(member);
+    }
+    (circle_id, creator, joined)
+}
+
+fn advance_ledgers(env: &Env, ledgers: u32) {
+    env.ledger().with_mut(|ledger| ledger.sequence_number += ledgers);
+}
+
+fn pass_betrayal_tenure(env: &Env) {
+    advance_ledgers(env, DEFAULT_MIN_BETRAYAL_TENURE + 1);
+}
 
 #[test]
 fn test() {
     let env = Env::default();
-    let contract_id = env.register(Contract, ());
+    let contract_id = env.register(Contract, (Address::generate(&env),));
     let client = ContractClient::new(&env, &contract_id);
 
     let words = client.hello(&String::from_str(&env, "Dev"));
@@ -19,3 +186,792 @@ fn test() {
         ]
     );
 }
+
+#[test]
+fn failed_payout_is_escrowed_and_claimable() {
+    let env = Env::default();
+    let (client, kale, mining, _) = setup(&env);
+    let (_, _, members) = create_circle(&env, &client, 2);
+    let first = members.get(0).unwrap();
+    let second = members.get(1).unwrap();
+
+    mining.set_reward(&first, &1, &100);
+    mining.set_reward(&second, &1, &100);
+    kale.set_blocked(&second, &true);
+
+    let result = client.harvest_and_distribute_all(&Address::generate(&env), &1);
+    assert_eq!(result.total_distributed, 200);
+    assert_eq!(kale.balance(&first), 100);
+    assert_eq!(kale.balance(&second), 0);
+    assert_eq!(client.get_pending_credit(&second), 100);
+
+    kale.set_blocked(&second, &false);
+    assert_eq!(client.claim_pending(&second), 100);
+    assert_eq!(kale.balance(&second), 100);
+    assert_eq!(client.get_pending_credit(&second), 0);
+    assert_eq!(client.try_claim_pending(&second), Err(Ok(Error::NothingToClaim)));
+}
+
+#[test]
+fn sweep_never_touches_member_liabilities() {
+    let env = Env::default();
+    let (client, kale, mining, admin) = setup(&env);
+    let (_, _, members) = create_circle(&env, &client, 1);
+    let member = members.get(0).unwrap();
+    assert_eq!(client.get_admin(), Some(admin.clone()));
+
+    mining.set_reward(&member, &1, &100);
+    kale.set_blocked(&member, &true);
+    client.harvest_and_distribute_all(&Address::generate(&env), &1);
+    assert_eq!(client.get_pending_credit(&member), 100);
+
+    // Only KALE above what members are owed can leave the contract
+    kale.mint(&client.address, &50);
+    assert_eq!(client.try_sweep(&kale.address, &admin, &51), Err(Ok(Error::SweepExceedsSurplus)));
+    client.sweep(&kale.address, &admin, &50);
+    assert_eq!(kale.balance(&admin), 50);
+    assert_eq!(kale.balance(&client.address), 100);
+}
+
+#[test]
+fn remainder_rotates_by_index_or_carries_forward() {
+    let env = Env::default();
+    let (client, kale, mining, _) = setup(&env);
+    let (circle_id, creator, members) = create_circle(&env, &client, 3);
+    let first = members.get(0).unwrap();
+    let second = members.get(1).unwrap();
+    let third = members.get(2).unwrap();
+
+    // 301 split three ways leaves 1, which goes to the member whose turn it is (index 4 -> second)
+    mining.set_reward(&first, &4, &101);
+    mining.set_reward(&second, &4, &100);
+    mining.set_reward(&third, &4, &100);
+    let result = client.harvest_and_distribute_all(&Address::generate(&env), &4);
+    let remainder = result.circles.get(0).unwrap().remainder;
+    assert_eq!(remainder.amount, 1);
+    assert_eq!(remainder.recipient, Some(second.clone()));
+    assert_eq!(kale.balance(&first), 100);
+    assert_eq!(kale.balance(&second), 101);
+    assert_eq!(kale.balance(&third), 100);
+
+    // Carried dust waits for the next harvest instead
+    client.set_remainder_strategy(&creator, &circle_id, &RemainderStrategy::CarryForward);
+    mining.set_reward(&first, &5, &101);
+    mining.set_reward(&second, &5, &100);
+    mining.set_reward(&third, &5, &100);
+    let result = client.harvest_and_distribute_all(&Address::generate(&env), &5);
+    assert!(result.circles.get(0).unwrap().remainder.carried_forward);
+    assert_eq!(client.get_carried_dust(&circle_id), 1);
+    assert_eq!(kale.balance(&first), 200);
+    assert_eq!(kale.balance(&second), 201);
+}
+
+// Harvest a two-member circle once and return what the creator and each member ended up with
+fn harvest_with_policy(policy: DistributionPolicy, rewards: [i128; 2], index: u32) -> [i128; 3] {
+    let env = Env::default();
+    let (client, kale, mining, _) = setup(&env);
+    let (_, creator, members) = create_circle_with_policy(&env, &client, policy, 2);
+    let first = members.get(0).unwrap();
+    let second = members.get(1).unwrap();
+
+    mining.set_reward(&first, &index, &rewards[0]);
+    mining.set_reward(&second, &index, &rewards[1]);
+    client.harvest_and_distribute_all(&Address::generate(&env), &index);
+    [kale.balance(&creator), kale.balance(&first), kale.balance(&second)]
+}
+
+#[test]
+fn creator_commission_is_taken_before_the_equal_split() {
+    assert_eq!(harvest_with_policy(DistributionPolicy::CreatorCommission(1_000), [100, 100], 1), [20, 90, 90]);
+}
+
+#[test]
+fn pro_rata_follows_what_each_member_harvested() {
+    assert_eq!(harvest_with_policy(DistributionPolicy::ProRata, [100, 300], 1), [0, 100, 300]);
+}
+
+#[test]
+fn winner_takes_all_rotates_by_index() {
+    assert_eq!(harvest_with_policy(DistributionPolicy::WinnerTakesAll, [100, 100], 2), [0, 200, 0]);
+    assert_eq!(harvest_with_policy(DistributionPolicy::WinnerTakesAll, [100, 100], 3), [0, 0, 200]);
+}
+
+#[test]
+fn policy_changes_wait_for_the_timelock() {
+    let env = Env::default();
+    let (client, _, _, _) = setup(&env);
+    let (circle_id, creator, _) = create_circle(&env, &client, 1);
+
+    client.queue_policy_change(&creator, &circle_id, &DistributionPolicy::WinnerTakesAll);
+    assert_eq!(client.try_apply_policy_change(&creator, &circle_id), Err(Ok(Error::PolicyTimelocked)));
+
+    advance_ledgers(&env, POLICY_TIMELOCK_LEDGERS);
+    client.apply_policy_change(&creator, &circle_id);
+    assert!(client.get_pending_policy(&circle_id).is_none());
+    assert_eq!(client.try_apply_policy_change(&creator, &circle_id), Err(Ok(Error::NoPendingPolicy)));
+}
+
+#[test]
+fn contributions_track_harvested_received_and_participation() {
+    let env = Env::default();
+    let (client, _, mining, _) = setup(&env);
+    let (circle_id, _, members) = create_circle(&env, &client, 2);
+    let miner = members.get(0).unwrap();
+    let free_rider = members.get(1).unwrap();
+
+    mining.set_reward(&miner, &1, &200);
+    client.harvest_and_distribute_all(&Address::generate(&env), &1);
+    mining.set_reward(&miner, &2, &100);
+    client.harvest_and_distribute_all(&Address::generate(&env), &2);
+
+    let mined = client.get_member_contribution(&circle_id, &miner).unwrap();
+    assert_eq!(mined.total_harvested, 300);
+    assert_eq!(mined.total_received, 150);
+    assert_eq!(mined.harvests_participated, 2);
+
+    let rode = client.get_member_contribution(&circle_id, &free_rider).unwrap();
+    assert_eq!(rode.total_harvested, 0);
+    assert_eq!(rode.total_received, 150);
+    assert_eq!(rode.harvests_participated, 0);
+
+    let breakdown = client.get_circle_contributions(&circle_id);
+    assert_eq!(breakdown.len(), 2);
+    assert_eq!(breakdown.get(0).unwrap().member, miner);
+}
+
+#[test]
+fn harvest_report_names_each_failure_and_its_reason() {
+    let env = Env::default();
+    let (client, kale, mining, _) = setup(&env);
+    let (_, _, members) = create_circle(&env, &client, 3);
+    let paid = members.get(0).unwrap();
+    let idle = members.get(1).unwrap();
+    let lapsed = members.get(2).unwrap();
+
+    mining.set_reward(&paid, &1, &100);
+    mining.set_reward(&lapsed, &1, &100);
+    kale.approve(&lapsed, &client.address, &0, &0);
+
+    let result = client.harvest_and_distribute_all(&Address::generate(&env), &1);
+    let report = result.circles.get(0).unwrap();
+    assert_eq!(report.pulls.len(), 1);
+    assert_eq!(report.pulls.get(0).unwrap().member, paid);
+    assert_eq!(report.pulls.get(0).unwrap().amount, 100);
+
+    assert_eq!(report.failures.len(), 2);
+    let mining_failure = report.failures.get(0).unwrap();
+    assert_eq!(mining_failure.member, idle);
+    assert_eq!(mining_failure.reason, HarvestFailureReason::MiningError);
+    let allowance_failure = report.failures.get(1).unwrap();
+    assert_eq!(allowance_failure.member, lapsed);
+    assert_eq!(allowance_failure.reason, HarvestFailureReason::AllowanceExpired);
+
+    // The member whose allowance ran out keeps their own harvest on top of their share of the pool
+    assert_eq!(kale.balance(&lapsed), 133);
+}
+
+#[test]
+fn preview_matches_the_harvest_without_moving_tokens() {
+    let env = Env::default();
+    let (client, kale, mining, _) = setup(&env);
+    let (circle_id, _, members) = create_circle(&env, &client, 3);
+    let first = members.get(0).unwrap();
+    let second = members.get(1).unwrap();
+    let third = members.get(2).unwrap();
+
+    let preview = client.preview_distribution(&circle_id, &301);
+    assert_eq!(preview.status, PreviewStatus::Ready);
+    assert_eq!(preview.total, 301);
+    assert_eq!(preview.payouts.len(), 3);
+    assert_eq!(preview.payouts.get(0).unwrap().amount, 100);
+    assert_eq!(preview.betrayal_recipient, None);
+    assert_eq!(preview.remainder.amount, 1);
+    assert_eq!(kale.balance(&client.address), 0);
+
+    mining.set_reward(&first, &4, &101);
+    mining.set_reward(&second, &4, &100);
+    mining.set_reward(&third, &4, &100);
+    let result = client.harvest_and_distribute_all(&Address::generate(&env), &4);
+    let report = result.circles.get(0).unwrap();
+    for (previewed, paid) in preview.payouts.iter().zip(report.payouts.iter()) {
+        assert_eq!(previewed.member, paid.member);
+        assert_eq!(previewed.amount, paid.amount);
+    }
+    assert_eq!(report.remainder.amount, preview.remainder.amount);
+}
+
+#[test]
+fn keepers_earn_a_capped_tip_per_call() {
+    let env = Env::default();
+    let (client, kale, mining, _) = setup(&env);
+    let (_, _, members) = create_circle(&env, &client, 2);
+    let first = members.get(0).unwrap();
+    let second = members.get(1).unwrap();
+    let keeper = Address::generate(&env);
+
+    assert_eq!(client.try_set_keeper_config(&301, &10), Err(Ok(Error::InvalidBasisPoints)));
+    client.set_keeper_config(&200, &3);
+
+    // 2% of 200 is 4, capped at 3 for the call
+    mining.set_reward(&first, &1, &100);
+    mining.set_reward(&second, &1, &100);
+    let result = client.harvest_and_distribute_all(&keeper, &1);
+    assert_eq!(result.circles.get(0).unwrap().keeper_tip, 3);
+    assert_eq!(kale.balance(&keeper), 3);
+    assert_eq!(kale.balance(&first) + kale.balance(&second), 197);
+
+    let stats = client.get_keeper_stats(&keeper).unwrap();
+    assert_eq!(stats.total_tips, 3);
+    assert_eq!(stats.harvest_calls, 1);
+    assert_eq!(stats.circles_harvested, 1);
+
+    let board = client.get_keeper_leaderboard(&10);
+    assert_eq!(board.len(), 1);
+    assert_eq!(board.get(0).unwrap().address, keeper);
+}
+
+#[test]
+fn harvesting_a_block_twice_is_a_no_op() {
+    let env = Env::default();
+    let (client, kale, mining, _) = setup(&env);
+    let (_, _, members) = create_circle(&env, &client, 1);
+    let member = members.get(0).unwrap();
+
+    mining.set_reward(&member, &7, &100);
+    let first = client.harvest_and_distribute_all(&Address::generate(&env), &7);
+    assert_eq!(first.total_distributed, 100);
+    assert!(client.is_index_harvested(&member, &7));
+
+    // Even if the mining contract would pay again, the block is not harvested a second time
+    mining.set_reward(&member, &7, &100);
+    let second = client.harvest_and_distribute_all(&Address::generate(&env), &7);
+    assert_eq!(second.total_distributed, 0);
+    assert_eq!(kale.balance(&member), 100);
+}
+
+#[test]
+fn failed_harvest_is_not_marked_and_can_be_retried() {
+    let env = Env::default();
+    let (client, kale, mining, _) = setup(&env);
+    let (_, _, members) = create_circle(&env, &client, 1);
+    let member = members.get(0).unwrap();
+
+    let first = client.harvest_and_distribute_all(&Address::generate(&env), &3);
+    assert_eq!(first.total_distributed, 0);
+    assert!(!client.is_index_harvested(&member, &3));
+
+    mining.set_reward(&member, &3, &100);
+    client.harvest_and_distribute_all(&Address::generate(&env), &3);
+    assert!(client.is_index_harvested(&member, &3));
+    assert_eq!(kale.balance(&member), 100);
+}
+
+#[test]
+fn circle_plants_only_for_consenting_members() {
+    let env = Env::default();
+    let (client, kale, _, _) = setup(&env);
+    let (circle_id, _, members) = create_circle(&env, &client, 2);
+    let willing = members.get(0).unwrap();
+    let unwilling = members.get(1).unwrap();
+    kale.mint(&willing, &50);
+    kale.mint(&unwilling, &50);
+
+    client.set_plant_consent(&willing, &circle_id, &true);
+    let statuses = client.plant_for_circle(&willing, &circle_id, &10);
+    assert!(statuses.get(0).unwrap().planted);
+    assert_eq!(statuses.get(0).unwrap().amount, 10);
+    assert!(!statuses.get(1).unwrap().planted);
+    assert!(!statuses.get(1).unwrap().consented);
+
+    let current = client.get_plant_status(&circle_id);
+    assert!(current.get(0).unwrap().planted);
+    assert!(!current.get(1).unwrap().planted);
+
+    // A new round starts everyone over, and a stake the member can't cover isn't planted
+    advance_ledgers(&env, 1);
+    client.plant_for_circle(&willing, &circle_id, &100);
+    assert!(!client.get_plant_status(&circle_id).get(0).unwrap().planted);
+    assert_eq!(client.try_plant_for_circle(&willing, &circle_id, &0).err(), Some(Ok(Error::InvalidAmount)));
+}
+
+#[test]
+fn work_batch_reports_each_farmer_and_survives_failures() {
+    let env = Env::default();
+    let (client, _, mining, _) = setup(&env);
+    let ready = Address::generate(&env);
+    let unplanted = Address::generate(&env);
+    let late = Address::generate(&env);
+    mining.set_zeros(&ready, &7);
+    mining.set_zeros(&late, &3);
+
+    let hash = BytesN::from_array(&env, &[0; 32]);
+    let entries = vec![
+        &env,
+        (ready.clone(), hash.clone(), 1u64),
+        (unplanted.clone(), hash.clone(), 2u64),
+        (late.clone(), hash, 3u64),
+    ];
+    let results = client.submit_work_batch(&Address::generate(&env), &entries);
+    assert_eq!(results.len(), 3);
+    assert_eq!((results.get(0).unwrap().farmer, results.get(0).unwrap().success, results.get(0).unwrap().zeros), (ready, true, 7));
+    assert_eq!((results.get(1).unwrap().farmer, results.get(1).unwrap().success, results.get(1).unwrap().zeros), (unplanted, false, 0));
+    assert_eq!((results.get(2).unwrap().farmer, results.get(2).unwrap().success, results.get(2).unwrap().zeros), (late, true, 3));
+}
+
+#[test]
+fn compounding_share_is_held_back_until_the_member_plants_it() {
+    let env = Env::default();
+    let (client, kale, mining, admin) = setup(&env);
+    let (circle_id, _, members) = create_circle(&env, &client, 1);
+    let member = members.get(0).unwrap();
+    client.set_compound_bps(&member, &circle_id, &2_500);
+
+    // A keeper's harvest can't plant for the member, so a quarter of the payout waits in the contract
+    mining.set_reward(&member, &1, &100);
+    let result = client.harvest_and_distribute_all(&Address::generate(&env), &1);
+    let payout = result.circles.get(0).unwrap().payouts.get(0).unwrap();
+    assert_eq!(payout.amount, 100);
+    assert_eq!(payout.compounded, 25);
+    assert_eq!(kale.balance(&member), 75);
+    assert_eq!(client.get_compound_reserve(&member), 25);
+    assert_eq!(client.try_sweep(&kale.address, &admin, &1), Err(Ok(Error::SweepExceedsSurplus)));
+
+    assert_eq!(client.plant_compounded(&member), 25);
+    assert_eq!(client.get_compound_reserve(&member), 0);
+    assert_eq!(client.get_player_earnings(&member).unwrap().kale_compounded, 25);
+    assert_eq!(client.try_plant_compounded(&member), Err(Ok(Error::NothingToClaim)));
+}
+
+#[test]
+fn vault_yield_raises_the_share_price() {
+    let env = Env::default();
+    let (client, kale, mining, _) = setup(&env);
+    let (circle_id, creator, members) = create_circle(&env, &client, 2);
+    let small = members.get(0).unwrap();
+    let large = members.get(1).unwrap();
+    kale.mint(&small, &100);
+    kale.mint(&large, &300);
+
+    assert_eq!(client.try_deposit(&small, &circle_id, &100), Err(Ok(Error::NotPooledCircle)));
+    client.enable_pooled_mode(&creator, &circle_id);
+    assert_eq!(client.deposit(&small, &circle_id, &100), 100);
+    assert_eq!(client.deposit(&large, &circle_id, &300), 300);
+
+    // Planted KALE can't be withdrawn until it comes back
+    client.vault_plant(&creator, &circle_id, &400);
+    assert_eq!(client.try_withdraw(&small, &circle_id, &100), Err(Ok(Error::VaultIlliquid)));
+    mining.set_zeros(&client.address, &6);
+    assert_eq!(client.vault_work(&small, &circle_id, &BytesN::from_array(&env, &[0; 32]), &1), 6);
+
+    mining.set_reward(&client.address, &1, &480);
+    assert_eq!(client.vault_harvest(&large, &circle_id, &1), 480);
+    let info = client.get_share_price(&circle_id);
+    assert_eq!(info.total_assets, 480);
+    assert_eq!(info.planted, 0);
+    assert_eq!(info.share_price, 12_000_000);
+
+    assert_eq!(client.withdraw(&small, &circle_id, &100), 120);
+    assert_eq!(kale.balance(&small), 120);
+    assert_eq!(client.get_vault_shares(&circle_id, &small), 0);
+    assert_eq!(client.get_vault_shares(&circle_id, &large), 300);
+}
+
+#[test]
+fn betrayal_settles_the_debt_then_members_keep_earning() {
+    let env = Env::default();
+    let (client, kale, mining, _) = setup(&env);
+    let (circle_id, creator, members) = create_circle(&env, &client, 2);
+    let betrayer = members.get(0).unwrap();
+    let loyal = members.get(1).unwrap();
+
+    client.set_betrayal_terms(&creator, &circle_id, &BetrayalTerms {
+        rule: BetrayalRule::TakeRecentHarvests(5_000),
+        after: AfterBetrayal::MembersKeepEarnings,
+    });
+
+    mining.set_reward(&betrayer, &1, &100);
+    mining.set_reward(&loyal, &1, &100);
+    client.harvest_and_distribute_all(&Address::generate(&env), &1);
+    assert_eq!(kale.balance(&betrayer), 100);
+    assert_eq!(kale.balance(&loyal), 100);
+
+    pass_betrayal_tenure(&env);
+    client.betray_circle(&betrayer, &circle_id, &String::from_str(&env, PASSWORD));
+    assert_eq!(client.get_betrayal_debt(&circle_id), 100);
+    assert_eq!(client.get_circle_betrayers(&circle_id), vec![&env, betrayer.clone()]);
+
+    // The betrayer is paid off first from the loyal member's harvest, the loyal member keeps the rest
+    mining.set_reward(&betrayer, &2, &50);
+    mining.set_reward(&loyal, &2, &300);
+    client.harvest_and_distribute_all(&Address::generate(&env), &2);
+    assert_eq!(client.get_betrayal_debt(&circle_id), 0);
+    assert_eq!(kale.balance(&betrayer), 200);
+    assert_eq!(kale.balance(&loyal), 300);
+
+    // The betrayer is no longer pulled, so their own harvests stay theirs to collect
+    mining.set_reward(&betrayer, &3, &70);
+    mining.set_reward(&loyal, &3, &100);
+    client.harvest_and_distribute_all(&Address::generate(&env), &3);
+    assert!(!client.is_index_harvested(&betrayer, &2));
+    assert!(!client.is_index_harvested(&betrayer, &3));
+    assert_eq!(kale.balance(&betrayer), 200);
+    assert_eq!(kale.balance(&loyal), 400);
+
+    assert_eq!(mining.harvest(&betrayer, &3), 70);
+    assert_eq!(kale.balance(&betrayer), 270);
+}
+
+#[test]
+fn only_members_past_the_minimum_tenure_can_betray() {
+    let env = Env::default();
+    let (client, _, _, _) = setup(&env);
+    let (circle_id, _, members) = create_circle(&env, &client, 1);
+    let member = members.get(0).unwrap();
+    let password = String::from_str(&env, PASSWORD);
+
+    // Knowing the password isn't enough
+    let stranger = Address::generate(&env);
+    assert_eq!(client.try_betray_circle(&stranger, &circle_id, &password), Err(Ok(Error::NotAMember)));
+
+    client.set_min_betrayal_tenure(&10);
+    assert_eq!(client.get_min_betrayal_tenure(), 10);
+    assert_eq!(client.try_betray_circle(&member, &circle_id, &password), Err(Ok(Error::BetrayalTooEarly)));
+
+    advance_ledgers(&env, 9);
+    assert_eq!(client.try_betray_circle(&member, &circle_id, &password), Err(Ok(Error::BetrayalTooEarly)));
+    advance_ledgers(&env, 1);
+    assert!(client.betray_circle(&member, &circle_id, &password));
+}
+
+fn betrayal_commitment(env: &Env, salt: &BytesN<32>, member: &Address) -> BytesN<32> {
+    let mut preimage = Bytes::from_array(env, &salt.to_array());
+    preimage.append(&member.clone().to_xdr(env));
+    env.crypto().sha256(&preimage).into()
+}
+
+#[test]
+fn sealed_betrayal_takes_effect_only_when_revealed() {
+    let env = Env::default();
+    let (client, _, _, _) = setup(&env);
+    let (circle_id, creator, members) = create_circle(&env, &client, 2);
+    let traitor = members.get(0).unwrap();
+    let kicked = members.get(1).unwrap();
+    let salt = BytesN::from_array(&env, &[7; 32]);
+    pass_betrayal_tenure(&env);
+
+    client.set_sealed_betrayal(&creator, &circle_id, &true, &50);
+    let password = String::from_str(&env, PASSWORD);
+    assert_eq!(client.try_betray_circle(&traitor, &circle_id, &password), Err(Ok(Error::SealedBetrayalOnly)));
+
+    let reveal_at = client.commit_betrayal(&traitor, &circle_id, &betrayal_commitment(&env, &salt, &traitor));
+    assert_eq!(reveal_at, env.ledger().sequence() + 50);
+    assert_eq!(client.try_reveal_betrayal(&traitor, &circle_id, &salt), Err(Ok(Error::RevealTooEarly)));
+
+    // A traitor kicked during the window can no longer reveal
+    client.commit_betrayal(&kicked, &circle_id, &betrayal_commitment(&env, &salt, &kicked));
+    client.kick_member(&creator, &circle_id, &kicked);
+
+    advance_ledgers(&env, 50);
+    assert_eq!(client.try_reveal_betrayal(&kicked, &circle_id, &salt), Err(Ok(Error::NoCommitment)));
+    assert_eq!(client.try_reveal_betrayal(&traitor, &circle_id, &BytesN::from_array(&env, &[8; 32])), Err(Ok(Error::CommitmentMismatch)));
+    assert!(!client.get_circle_info(&circle_id).unwrap().1);
+
+    assert!(client.reveal_betrayal(&traitor, &circle_id, &salt));
+    assert!(client.get_circle_info(&circle_id).unwrap().1);
+    assert_eq!(client.get_circle_betrayers(&circle_id), vec![&env, traitor]);
+}
+
+#[test]
+fn betrayers_within_the_window_share_the_spoils() {
+    let env = Env::default();
+    let (client, kale, mining, _) = setup(&env);
+    let (circle_id, creator, members) = create_circle(&env, &client, 3);
+    let first = members.get(0).unwrap();
+    let second = members.get(1).unwrap();
+    let loyal = members.get(2).unwrap();
+    let password = String::from_str(&env, PASSWORD);
+    client.set_betrayal_window(&creator, &circle_id, &BetrayalWindow { window_ledgers: 100, rule: MultiBetrayalRule::ShareSpoils });
+    pass_betrayal_tenure(&env);
+
+    client.betray_circle(&first, &circle_id, &password);
+    advance_ledgers(&env, 10);
+    client.betray_circle(&second, &circle_id, &password);
+    assert_eq!(client.get_circle_betrayers(&circle_id), vec![&env, first.clone(), second.clone()]);
+    assert_eq!(client.get_player_stats(&second).unwrap().circles_betrayed, 1);
+
+    // Payouts wait for the window to close, then are split between both betrayers
+    mining.set_reward(&loyal, &1, &200);
+    client.harvest_and_distribute_all(&Address::generate(&env), &1);
+    assert_eq!(client.get_window_held_rewards(&circle_id), 200);
+    assert_eq!(client.try_settle_betrayal_window(&circle_id), Err(Ok(Error::BetrayalWindowOpen)));
+
+    advance_ledgers(&env, 100);
+    assert_eq!(client.settle_betrayal_window(&circle_id), 200);
+    assert_eq!(kale.balance(&first), 100);
+    assert_eq!(kale.balance(&second), 100);
+}
+
+#[test]
+fn full_defection_voids_the_spoils_back_to_the_creator() {
+    let env = Env::default();
+    let (client, kale, mining, admin) = setup(&env);
+    let (circle_id, creator, members) = create_circle(&env, &client, 2);
+    let first = members.get(0).unwrap();
+    let second = members.get(1).unwrap();
+    let password = String::from_str(&env, PASSWORD);
+    client.set_betrayal_window(&creator, &circle_id, &BetrayalWindow { window_ledgers: 100, rule: MultiBetrayalRule::PrisonersDilemma });
+    pass_betrayal_tenure(&env);
+
+    client.betray_circle(&first, &circle_id, &password);
+    mining.set_reward(&second, &1, &150);
+    client.harvest_and_distribute_all(&Address::generate(&env), &1);
+    assert_eq!(client.get_window_held_rewards(&circle_id), 150);
+
+    // The last member defects too, so nobody gets anything and the circle freezes
+    client.betray_circle(&second, &circle_id, &password);
+    assert!(client.is_circle_frozen(&circle_id));
+    assert_eq!(client.get_window_held_rewards(&circle_id), 0);
+    assert_eq!(kale.balance(&first), 0);
+    assert_eq!(kale.balance(&second), 0);
+    assert_eq!(kale.balance(&creator), 150);
+    assert_eq!(client.try_sweep(&kale.address, &admin, &1), Err(Ok(Error::SweepExceedsSurplus)));
+}
+
+#[test]
+fn mutiny_betrays_once_the_quorum_votes_within_the_window() {
+    let env = Env::default();
+    let (client, _, _, _) = setup(&env);
+    let (circle_id, creator, members) = create_circle(&env, &client, 4);
+    let first = members.get(0).unwrap();
+    let second = members.get(1).unwrap();
+    let third = members.get(2).unwrap();
+    client.set_mutiny_mode(&creator, &circle_id, &Some(MutinyConfig { quorum_bps: 5_000, voting_window: 100 }));
+    pass_betrayal_tenure(&env);
+
+    assert_eq!(client.try_betray_circle(&first, &circle_id, &String::from_str(&env, PASSWORD)), Err(Ok(Error::MutinyOnly)));
+
+    assert!(!client.vote_betray(&first, &circle_id));
+    let status = client.get_mutiny_status(&circle_id).unwrap();
+    assert!(status.active);
+    assert_eq!(status.votes_needed, 2);
+    assert_eq!(status.voters, vec![&env, first.clone()]);
+    assert_eq!(client.try_vote_betray(&first, &circle_id), Err(Ok(Error::AlreadyVoted)));
+
+    // A vote that doesn't reach quorum in time expires and the next one starts over
+    advance_ledgers(&env, 101);
+    assert!(!client.get_mutiny_status(&circle_id).unwrap().active);
+    assert!(!client.vote_betray(&second, &circle_id));
+    assert_eq!(client.get_mutiny_status(&circle_id).unwrap().voters, vec![&env, second.clone()]);
+
+    assert!(client.vote_betray(&third, &circle_id));
+    assert!(client.get_circle_info(&circle_id).unwrap().1);
+    assert_eq!(client.get_circle_betrayers(&circle_id), vec![&env, second, third]);
+}
+
+#[test]
+fn mutiny_voters_cannot_release_their_deposit_before_quorum() {
+    let env = Env::default();
+    let (client, kale, _, _) = setup(&env);
+    let (circle_id, creator, members) = create_circle(&env, &client, 3);
+    let first = members.get(0).unwrap();
+    let second = members.get(1).unwrap();
+    let loyal = members.get(2).unwrap();
+    kale.mint(&first, &20);
+    kale.mint(&second, &20);
+    client.set_revenge_terms(&creator, &circle_id, &Some(RevengeTerms { window_ledgers: 100, clawback_bps: 5_000, deposit: 20 }));
+    client.set_mutiny_mode(&creator, &circle_id, &Some(MutinyConfig { quorum_bps: 6_000, voting_window: 100 }));
+    pass_betrayal_tenure(&env);
+
+    client.vote_betray(&first, &circle_id);
+    assert_eq!(client.try_release_betrayal_escrow(&first, &circle_id), Err(Ok(Error::CircleNotBetrayed)));
+    assert_eq!(client.get_betrayal_escrow(&circle_id, &first), (0, 20));
+
+    // The early voter is still a mutineer once the vote passes, and their deposit can be slashed
+    assert!(client.vote_betray(&second, &circle_id));
+    assert_eq!(client.get_circle_betrayers(&circle_id), vec![&env, first.clone(), second.clone()]);
+    assert_eq!(client.try_release_betrayal_escrow(&loyal, &circle_id), Err(Ok(Error::NotABetrayer)));
+    assert_eq!(client.try_release_betrayal_escrow(&first, &circle_id), Err(Ok(Error::RevengeWindowOpen)));
+
+    assert_eq!(client.retaliate(&loyal, &circle_id), 40);
+    assert_eq!(kale.balance(&creator), 20);
+    assert_eq!(kale.balance(&loyal), 20);
+    assert_eq!(client.try_release_betrayal_escrow(&first, &circle_id), Err(Ok(Error::NothingToClaim)));
+}
+
+#[test]
+fn retaliation_claws_back_held_rewards_and_slashes_the_deposit() {
+    let env = Env::default();
+    let (client, kale, mining, _) = setup(&env);
+    let (circle_id, creator, members) = create_circle(&env, &client, 2);
+    let betrayer = members.get(0).unwrap();
+    let loyal = members.get(1).unwrap();
+    kale.mint(&betrayer, &10);
+    client.set_revenge_terms(&creator, &circle_id, &Some(RevengeTerms { window_ledgers: 100, clawback_bps: 5_000, deposit: 10 }));
+    pass_betrayal_tenure(&env);
+
+    assert_eq!(client.try_retaliate(&loyal, &circle_id), Err(Ok(Error::RevengeWindowClosed)));
+    client.betray_circle(&betrayer, &circle_id, &String::from_str(&env, PASSWORD));
+
+    // The betrayer's take is held while victims can still retaliate
+    mining.set_reward(&loyal, &1, &100);
+    client.harvest_and_distribute_all(&Address::generate(&env), &1);
+    assert_eq!(client.get_betrayal_escrow(&circle_id, &betrayer), (100, 10));
+    assert_eq!(kale.balance(&betrayer), 0);
+
+    assert_eq!(client.try_retaliate(&Address::generate(&env), &circle_id), Err(Ok(Error::NotAMember)));
+    assert_eq!(client.retaliate(&creator, &circle_id), 60);
+    assert!(client.has_retaliated(&circle_id));
+    assert_eq!(kale.balance(&creator), 30);
+    assert_eq!(kale.balance(&loyal), 30);
+    assert_eq!(client.try_retaliate(&loyal, &circle_id), Err(Ok(Error::AlreadyRetaliated)));
+
+    assert_eq!(client.release_betrayal_escrow(&betrayer, &circle_id), 50);
+    assert_eq!(kale.balance(&betrayer), 50);
+}
+
+#[test]
+fn join_bond_is_refunded_on_leave_and_forfeited_on_betrayal() {
+    let env = Env::default();
+    let (client, kale, _, _) = setup(&env);
+    let (circle_id, creator, _) = create_circle(&env, &client, 0);
+    client.set_join_bond(&creator, &circle_id, &50);
+
+    let betrayer = Address::generate(&env);
+    let loyal = Address::generate(&env);
+    let leaver = Address::generate(&env);
+    for member in [&betrayer, &loyal, &leaver] {
+        kale.mint(member, &50);
+        client.join_circle(member, &circle_id, &String::from_str(&env, PASSWORD));
+        assert_eq!(kale.balance(member), 0);
+        assert_eq!(client.get_member_bond(&circle_id, member), 50);
+    }
+
+    client.leave_circle(&leaver, &circle_id);
+    assert_eq!(kale.balance(&leaver), 50);
+    assert_eq!(client.get_member_bond(&circle_id, &leaver), 0);
+
+    // The betrayer's bond compensates the members who stayed
+    pass_betrayal_tenure(&env);
+    client.betray_circle(&betrayer, &circle_id, &String::from_str(&env, PASSWORD));
+    assert_eq!(client.get_member_bond(&circle_id, &betrayer), 0);
+    assert_eq!(kale.balance(&betrayer), 0);
+    assert_eq!(kale.balance(&loyal), 50);
+    assert_eq!(client.get_member_bond(&circle_id, &loyal), 50);
+}
+
+#[test]
+fn restitution_pays_the_victims_and_restores_up_to_half_the_trust() {
+    let env = Env::default();
+    let (client, kale, _, _) = setup(&env);
+    let (circle_id, creator, members) = create_circle(&env, &client, 2);
+    let betrayer = members.get(0).unwrap();
+    let loyal = members.get(1).unwrap();
+    kale.mint(&betrayer, &(4 * TRUST_KALE_UNIT));
+    pass_betrayal_tenure(&env);
+
+    assert_eq!(client.try_pay_restitution(&betrayer, &circle_id, &TRUST_KALE_UNIT), Err(Ok(Error::NotABetrayer)));
+    client.betray_circle(&betrayer, &circle_id, &String::from_str(&env, PASSWORD));
+    let betrayed = client.get_trust_record(&betrayer).score;
+
+    assert_eq!(client.pay_restitution(&betrayer, &circle_id, &(TRUST_KALE_UNIT / 4)), TRUST_KALE_UNIT / 4);
+    assert_eq!(client.get_trust_record(&betrayer).score, betrayed + TRUST_SCALE / 4);
+    assert_eq!(kale.balance(&creator), TRUST_KALE_UNIT / 8);
+    assert_eq!(kale.balance(&loyal), TRUST_KALE_UNIT / 8);
+
+    // Paying more keeps compensating the victims, but only half of the betrayal's cost can be bought back
+    assert_eq!(client.pay_restitution(&betrayer, &circle_id, &(2 * TRUST_KALE_UNIT)), 9 * TRUST_KALE_UNIT / 4);
+    assert_eq!(client.get_trust_record(&betrayer).score, betrayed + TRUST_SCALE / 2);
+    assert_eq!(client.get_restitution(&circle_id, &betrayer), 9 * TRUST_KALE_UNIT / 4);
+    assert_eq!(kale.balance(&loyal), 9 * TRUST_KALE_UNIT / 8);
+}
+
+#[test]
+fn refounded_circle_takes_over_the_loyal_members_harvests() {
+    let env = Env::default();
+    let (client, kale, mining, _) = setup(&env);
+    let (circle_id, creator, members) = create_circle(&env, &client, 2);
+    let betrayer = members.get(0).unwrap();
+    let loyal = members.get(1).unwrap();
+
+    pass_betrayal_tenure(&env);
+    client.betray_circle(&betrayer, &circle_id, &String::from_str(&env, PASSWORD));
+
+    let successor = client.refound_circle(&creator, &circle_id, &password_hash(&env));
+    assert!(client.is_circle_closed(&circle_id));
+    assert_eq!(client.get_circle_members(&successor), vec![&env, loyal.clone()]);
+    assert_eq!(client.get_circle_lineage(&successor), vec![&env, circle_id, successor]);
+
+    // The betrayed circle no longer redirects the loyal member's harvest to the betrayer
+    mining.set_reward(&loyal, &5, &100);
+    client.harvest_and_distribute_all(&Address::generate(&env), &5);
+    assert_eq!(kale.balance(&betrayer), 0);
+    assert_eq!(kale.balance(&loyal), 100);
+    assert_eq!(client.get_circle_earnings(&successor).unwrap().total_earned, 100);
+    assert_eq!(client.get_circle_earnings(&circle_id).unwrap().total_earned, 0);
+}
+
+#[test]
+fn rejoining_a_circle_does_not_earn_join_trust_again() {
+    let env = Env::default();
+    let (client, _, _, _) = setup(&env);
+    let (circle_id, _, members) = create_circle(&env, &client, 1);
+    let member = members.get(0).unwrap();
+    let password = String::from_str(&env, PASSWORD);
+    let joined = client.get_trust_record(&member).score;
+    assert_eq!(joined, TRUST_SCALE);
+
+    for _ in 0..3 {
+        client.leave_circle(&member, &circle_id);
+        client.join_circle(&member, &circle_id, &password);
+    }
+    assert_eq!(client.get_trust_record(&member).score, joined);
+
+    // Cycling can't lift a player over a circle's trust requirement, but joining another circle still counts
+    let (other_id, other_creator, _) = create_circle(&env, &client, 0);
+    client.set_min_trust(&other_creator, &other_id, &Some(2));
+    assert_eq!(client.try_join_circle(&member, &other_id, &password), Err(Ok(Error::TrustTooLow)));
+    client.set_min_trust(&other_creator, &other_id, &None);
+    client.join_circle(&member, &other_id, &password);
+    assert_eq!(client.get_trust_score(&member), 2);
+}
+
+#[test]
+fn leaderboards_are_sorted_and_paged() {
+    let env = Env::default();
+    let (client, _, mining, _) = setup(&env);
+    let (_, _, members) = create_circle_with_policy(&env, &client, DistributionPolicy::ProRata, 3);
+    let low = members.get(0).unwrap();
+    let high = members.get(1).unwrap();
+    let middle = members.get(2).unwrap();
+
+    mining.set_reward(&low, &1, &100);
+    mining.set_reward(&high, &1, &300);
+    mining.set_reward(&middle, &1, &200);
+    client.harvest_and_distribute_all(&Address::generate(&env), &1);
+
+    let board = client.get_leaderboard(&LeaderboardMetric::KaleEarned, &0, &10);
+    assert_eq!(board.len(), 3);
+    assert_eq!(board.get(0).unwrap().subject, LeaderboardSubject::Player(high.clone()));
+    assert_eq!(board.get(0).unwrap().value, 300);
+    assert_eq!(board.get(1).unwrap().subject, LeaderboardSubject::Player(middle.clone()));
+    assert_eq!(board.get(2).unwrap().subject, LeaderboardSubject::Player(low.clone()));
+
+    let page = client.get_leaderboard(&LeaderboardMetric::KaleEarned, &1, &1);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get(0).unwrap().subject, LeaderboardSubject::Player(middle));
+    assert!(client.get_leaderboard(&LeaderboardMetric::KaleEarned, &3, &10).is_empty());
+
+    // A later harvest moves the low earner up past the others
+    mining.set_reward(&low, &2, &300);
+    client.harvest_and_distribute_all(&Address::generate(&env), &2);
+    let board = client.get_leaderboard(&LeaderboardMetric::KaleEarned, &0, &2);
+    assert_eq!(board.get(0).unwrap().subject, LeaderboardSubject::Player(low));
+    assert_eq!(board.get(0).unwrap().value, 400);
+    assert_eq!(board.get(1).unwrap().subject, LeaderboardSubject::Player(high));
+
+    // Boards live in persistent storage, not in the contract instance
+    env.as_contract(&client.address, || {
+        assert!(env.storage().persistent().has(&DataKey::Leaderboard(LeaderboardMetric::KaleEarned)));
+        assert!(!env.storage().instance().has(&DataKey::Leaderboard(LeaderboardMetric::KaleEarned)));
+    });
+}